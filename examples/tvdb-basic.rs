@@ -15,36 +15,33 @@ impl From<tvdb::TvdbError> for MyError {
     }
 }
 
-/*
 fn lookup_tvdb(series: &str, season: u32, episode: u32) -> Result<String, MyError> {
     // Create API with your API key
     let api = tvdb::Tvdb::new("0629B785CE550C8D");
-    api.login().unwrap();
 
-    // Perform search (returns a vector of SeriesSearchResult's)
-    let lang = "en"; // Search for English show
-    let sr = api.search(series)?;
+    // Perform search (returns a vector of SeriesSearchData's)
+    let sr = api.search(Some(series), None)?;
 
-    if sr.data.len() > 0 {
-        // Look up episode based on reference to first result (the API automatically creates an
-        // tvdb::EpisodeId from the `SeriesSearchResult` which the `search` method returns)
-        let ep = try!(api.episode(123));// FIXME &sr.data[0], season, episode));
+    match sr.data {
+        Some(ref data) if !data.is_empty() => {
+            // Look up episode based on reference to first result (the API automatically creates an
+            // tvdb::SeriesId from the `SeriesSearchData` which the `search` method returns)
+            let ep = api.season_episode(&data[0], season, episode)?;
 
-        // Return episode name
-        return Ok(ep.episode_name);
-    } else {
-        // No search results, return error
-        return Err(MyError::NotFound);
+            // Return episode name
+            Ok(ep.episode_name)
+        }
+        _ => {
+            // No search results, return error
+            Err(MyError::NotFound)
+        }
     }
 }
-*/
 
 fn main() {
-    // FIXME
-    /*
     match lookup_tvdb("Scrubs", 1, 22) {
         Ok(name) => println!("Success: {}", name),
-        Err(e) => println!("Error: {:?}", e),
+        Err(MyError::ErrorFromTvdb { reason }) => println!("Error: {}", reason),
+        Err(MyError::NotFound) => println!("Error: series not found"),
     }
-    */
 }
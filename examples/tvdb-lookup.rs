@@ -21,5 +21,23 @@ fn main() {
 
     // Construct API object
     let api = tvdb::Tvdb::new("0629B785CE550C8D");
-    // FIXME
+
+    // Search for series
+    let sr = api.search(Some(&series_name), None).unwrap();
+    let first = match sr.data {
+        Some(ref data) if !data.is_empty() => &data[0],
+        _ => {
+            println!("No series found for {:?}", series_name);
+            return;
+        }
+    };
+
+    // Lookup episode
+    match api.season_episode(first, season_no, episode_no) {
+        Ok(ep) => println!(
+            "{} - S{:02}E{:02} - {}",
+            first.series_name, season_no, episode_no, ep.episode_name
+        ),
+        Err(e) => println!("Error: {}", e),
+    }
 }
@@ -0,0 +1,317 @@
+//! Resolving and downloading artwork (banners, posters, episode images etc)
+
+use std::fmt::Debug;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::data::{Actor, Episode, Image, Series, SeriesId, SeriesSearchData};
+use crate::error::{TvdbError, TvdbResult};
+use crate::raw::{DefaultHttpClient, Tvdb};
+
+/// Host TheTVDB serves artwork from by default
+pub const DEFAULT_ARTWORK_BASE_URL: &str = "https://artworks.thetvdb.com/banners/";
+
+/// Which variant of a piece of artwork to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtworkSize {
+    /// Full size image
+    Original,
+    /// Smaller, cached copy of the image
+    Thumbnail,
+}
+
+/// Turns the relative artwork paths found in API responses (such as
+/// `graphical/76156-g.jpg`) into full URLs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtworkHost {
+    base_url: String,
+}
+
+impl ArtworkHost {
+    /// Use the given base URL, e.g `https://artworks.thetvdb.com/banners/`
+    pub fn new<S>(base_url: S) -> ArtworkHost
+    where
+        S: Into<String>,
+    {
+        let mut base_url = base_url.into();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+        ArtworkHost { base_url }
+    }
+
+    /// Base URL artwork paths are relative to
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Full URL for the given artwork path. Paths which are already full
+    /// URLs are returned unchanged
+    pub fn url(&self, path: &str, size: ArtworkSize) -> String {
+        if path.starts_with("http://") || path.starts_with("https://") {
+            return path.to_owned();
+        }
+
+        let path = path.trim_start_matches('/');
+        match size {
+            ArtworkSize::Thumbnail if !path.starts_with("_cache/") => {
+                format!("{}_cache/{}", self.base_url, path)
+            }
+            _ => format!("{}{}", self.base_url, path),
+        }
+    }
+
+    /// Like `url`, but for optional paths as found in the data types. Empty
+    /// paths are treated as missing
+    fn url_opt(&self, path: &Option<String>, size: ArtworkSize) -> Option<String> {
+        match *path {
+            Some(ref p) if !p.is_empty() => Some(self.url(p, size)),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ArtworkHost {
+    fn default() -> ArtworkHost {
+        ArtworkHost::new(DEFAULT_ARTWORK_BASE_URL)
+    }
+}
+
+impl SeriesSearchData {
+    /// URL of the series banner
+    pub fn banner_url(&self, host: &ArtworkHost, size: ArtworkSize) -> Option<String> {
+        host.url_opt(&self.banner, size)
+    }
+}
+
+impl Series {
+    /// URL of the series banner
+    pub fn banner_url(&self, host: &ArtworkHost, size: ArtworkSize) -> Option<String> {
+        host.url_opt(&self.banner, size)
+    }
+
+    /// URL of the series poster
+    pub fn poster_url(&self, host: &ArtworkHost, size: ArtworkSize) -> Option<String> {
+        host.url_opt(&self.poster, size)
+    }
+
+    /// URL of the series fanart
+    pub fn fanart_url(&self, host: &ArtworkHost, size: ArtworkSize) -> Option<String> {
+        host.url_opt(&self.fanart, size)
+    }
+}
+
+impl Episode {
+    /// URL of the episode image
+    pub fn image_url(&self, host: &ArtworkHost, size: ArtworkSize) -> Option<String> {
+        host.url_opt(&self.filename, size)
+    }
+}
+
+impl Actor {
+    /// URL of the actor's image
+    pub fn image_url(&self, host: &ArtworkHost, size: ArtworkSize) -> Option<String> {
+        host.url_opt(&self.image, size)
+    }
+}
+
+impl Image {
+    /// Remote path of the given variant, along with the size it should be
+    /// resolved as. Thumbnail paths provided by TheTVDB already point at the
+    /// thumbnail
+    fn remote_path(&self, size: ArtworkSize) -> Option<(&str, ArtworkSize)> {
+        let file_name = match self.file_name {
+            Some(ref f) if !f.is_empty() => Some((f.as_str(), size)),
+            _ => None,
+        };
+        match (size, self.thumbnail.as_ref()) {
+            (ArtworkSize::Thumbnail, Some(t)) if !t.is_empty() => {
+                Some((t.as_str(), ArtworkSize::Original))
+            }
+            _ => file_name,
+        }
+    }
+
+    /// URL of the image. For thumbnails the `thumbnail` path is preferred
+    /// when TheTVDB provides one
+    pub fn url(&self, host: &ArtworkHost, size: ArtworkSize) -> Option<String> {
+        self.remote_path(size)
+            .map(|(path, size)| host.url(path, size))
+    }
+
+    /// Path, relative to a download directory, the image is stored at. Images
+    /// are grouped by series ID, key type and resolution, e.g
+    /// `76156/poster/680x1000/76156-1.jpg`. Thumbnails are stored in place of
+    /// the resolution under `thumbnail`. As these come from the server, key
+    /// types and resolutions which aren't a plain file name are replaced as if
+    /// missing, and `None` is returned for such file names
+    pub fn local_path<S>(&self, series: S, size: ArtworkSize) -> Option<PathBuf>
+    where
+        S: Into<SeriesId>,
+    {
+        let (remote, _) = self.remote_path(size)?;
+        let file_name = remote.rsplit('/').next().and_then(path_component)?;
+
+        let variant = match size {
+            ArtworkSize::Thumbnail => "thumbnail",
+            ArtworkSize::Original => self
+                .resolution
+                .as_ref()
+                .and_then(|r| path_component(r))
+                .unwrap_or("original"),
+        };
+        let key_type = self
+            .key_type
+            .as_ref()
+            .and_then(|k| path_component(k))
+            .unwrap_or("unknown");
+
+        let mut path = PathBuf::from(series.into().seriesid.to_string());
+        path.push(key_type);
+        path.push(variant);
+        path.push(file_name);
+        Some(path)
+    }
+}
+
+/// The given string if it is safe to use as a single path component, i.e
+/// not empty, `.` or `..`, and without path separators
+fn path_component(s: &str) -> Option<&str> {
+    if s.is_empty() || s == "." || s == ".." || s.contains('/') || s.contains('\\') {
+        None
+    } else {
+        Some(s)
+    }
+}
+
+/// Trait for custom implementations of artwork downloading
+pub trait DownloadClient: Debug + Send + Sync {
+    /// Fetch the given URL, streaming the response body into `dest`.
+    /// Returns the number of bytes written
+    fn download(&self, url: &str, dest: &mut dyn Write) -> TvdbResult<u64>;
+}
+
+impl DownloadClient for DefaultHttpClient {
+    fn download(&self, url: &str, dest: &mut dyn Write) -> TvdbResult<u64> {
+        let mut resp =
+            self.client()?
+                .get(url)
+                .send()
+                .map_err(|x| TvdbError::CommunicationError {
+                    reason: format!("Error creating HTTP request: {}", x),
+                })?;
+
+        // Check response
+        if !resp.status().is_success() {
+            return Err(TvdbError::CommunicationError {
+                reason: format!(
+                    "Unsuccessful HTTP response from url {}: {}",
+                    url,
+                    resp.status()
+                ),
+            });
+        }
+
+        resp.copy_to(dest)
+            .map_err(|x| TvdbError::CommunicationError {
+                reason: format!("Error reading response: {}", x),
+            })
+    }
+}
+
+/// Result of downloading artwork to a directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadOutcome {
+    /// Artwork was downloaded to the given path
+    Downloaded { path: PathBuf, bytes: u64 },
+    /// File already existed, so was not downloaded again
+    Skipped { path: PathBuf },
+}
+
+impl DownloadOutcome {
+    /// Location of the file
+    pub fn path(&self) -> &Path {
+        match *self {
+            DownloadOutcome::Downloaded { ref path, .. } => path,
+            DownloadOutcome::Skipped { ref path } => path,
+        }
+    }
+}
+
+impl<'a> Tvdb<'a> {
+    /// Download the artwork at the given path (as found in the data types, or
+    /// a full URL) into `dest`. Returns the number of bytes written
+    pub fn download_artwork(
+        &self,
+        path: &str,
+        size: ArtworkSize,
+        dest: &mut dyn Write,
+    ) -> TvdbResult<u64> {
+        let url = self.artwork_host().url(path, size);
+        self.download_client().download(&url, dest)
+    }
+
+    /// Download artwork to the given file, unless it already exists. The
+    /// download is written to a `.part` file first, so interrupted downloads
+    /// are not mistaken for complete ones
+    pub fn download_artwork_to_file(
+        &self,
+        path: &str,
+        size: ArtworkSize,
+        target: &Path,
+    ) -> TvdbResult<DownloadOutcome> {
+        if target.exists() {
+            return Ok(DownloadOutcome::Skipped {
+                path: target.to_owned(),
+            });
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut partial = target.as_os_str().to_owned();
+        partial.push(".part");
+        let partial = PathBuf::from(partial);
+
+        let bytes = {
+            let mut f = fs::File::create(&partial)?;
+            match self.download_artwork(path, size, &mut f) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    drop(f);
+                    let _ = fs::remove_file(&partial);
+                    return Err(e);
+                }
+            }
+        };
+        fs::rename(&partial, target)?;
+
+        Ok(DownloadOutcome::Downloaded {
+            path: target.to_owned(),
+            bytes,
+        })
+    }
+
+    /// Download a series image into `dir`, at the location given by
+    /// `Image::local_path`. Existing files are not downloaded again
+    pub fn download_image<S>(
+        &self,
+        series: S,
+        image: &Image,
+        size: ArtworkSize,
+        dir: &Path,
+    ) -> TvdbResult<DownloadOutcome>
+    where
+        S: Into<SeriesId>,
+    {
+        let missing = || TvdbError::DataError {
+            reason: format!("Image {:?} has no usable file name", image.id),
+        };
+        let (remote, remote_size) = image.remote_path(size).ok_or_else(missing)?;
+        let local = image.local_path(series, size).ok_or_else(missing)?;
+
+        self.download_artwork_to_file(remote, remote_size, &dir.join(local))
+    }
+}
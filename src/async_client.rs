@@ -0,0 +1,588 @@
+//! Asynchronous variant of `Tvdb`, enabled with the `async` feature.
+//!
+//! `AsyncTvdb` performs the same requests as `Tvdb` and returns the same
+//! data types, but through an `AsyncRequestClient`, so it can be used from
+//! async code without blocking the executor. The default client uses the
+//! async `reqwest` client, which must run within a Tokio runtime.
+
+use std::convert::TryInto;
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+
+use futures_util::lock::Mutex;
+use serde::de::DeserializeOwned;
+
+use crate::auth::{Auth, Token};
+use crate::data::*;
+use crate::error::{TvdbError, TvdbResult};
+use crate::raw::{
+    check_response, first_episode_id, language_codes, login_request, merge_updated, parse_base_url,
+    parse_token, season_episode_query, updated_windows, with_token, Endpoint, Fallback, Method,
+    Request, Response, TokenRenewal, DEFAULT_API_BASE_URL,
+};
+
+/// Boxed future returned by `AsyncRequestClient`
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Trait for custom implementations of performing HTTP requests
+/// asynchronously, the counterpart of `RequestClient`
+pub trait AsyncRequestClient: Debug + Send + Sync {
+    fn request<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, TvdbResult<Response>>;
+}
+
+/// Default implementation of AsyncRequestClient
+#[derive(Debug, Default)]
+pub struct DefaultAsyncHttpClient {
+    client: reqwest::Client,
+}
+
+impl AsyncRequestClient for DefaultAsyncHttpClient {
+    fn request<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, TvdbResult<Response>> {
+        Box::pin(async move {
+            let method = match request.method {
+                Method::Get => reqwest::Method::GET,
+                Method::Post => reqwest::Method::POST,
+                Method::Put => reqwest::Method::PUT,
+                Method::Delete => reqwest::Method::DELETE,
+            };
+
+            let mut req = self.client.request(method, &request.url);
+            for (name, value) in &request.headers {
+                req = req.header(name.as_str(), value.as_str());
+            }
+            if let Some(ref body) = request.body {
+                req = req.body(body.clone());
+            }
+
+            // Send request
+            let resp = req
+                .send()
+                .await
+                .map_err(|x| TvdbError::CommunicationError {
+                    reason: format!("Error creating HTTP request: {}", x),
+                })?;
+
+            let status = resp.status().as_u16();
+            let headers = resp
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.as_str().to_owned(),
+                        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                    )
+                })
+                .collect();
+
+            let body = resp
+                .text()
+                .await
+                .map_err(|x| TvdbError::CommunicationError {
+                    reason: format!("Error reading response: {}", x),
+                })?;
+
+            Ok(Response {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+}
+
+/// Asynchronous interface, with the same methods as `Tvdb`. Requests are
+/// built the same way as by `Tvdb`, only sent through an
+/// `AsyncRequestClient`. Token stores and artwork downloads are only
+/// available on `Tvdb`
+#[derive(Debug)]
+pub struct AsyncTvdb<'a> {
+    auth: Auth,
+    http_client: Option<&'a dyn AsyncRequestClient>,
+    api_base_url: String,
+    language: String,
+    language_fallback: Vec<String>,
+    auto_login: bool,
+    jwt_token: RwLock<Option<Token>>,
+    token_lock: Mutex<()>,
+    default_client: Arc<dyn AsyncRequestClient>,
+}
+
+impl<'a> Clone for AsyncTvdb<'a> {
+    fn clone(&self) -> AsyncTvdb<'a> {
+        AsyncTvdb {
+            auth: self.auth.clone(),
+            http_client: self.http_client,
+            api_base_url: self.api_base_url.clone(),
+            language: self.language.clone(),
+            language_fallback: self.language_fallback.clone(),
+            auto_login: self.auto_login,
+            jwt_token: RwLock::new(self.get_token()),
+            token_lock: Mutex::new(()),
+            default_client: self.default_client.clone(),
+        }
+    }
+}
+
+impl<'a> AsyncTvdb<'a> {
+    /// Initalise API with the given API key, or `Auth` to also log in as a
+    /// user
+    pub fn new<A>(auth: A) -> AsyncTvdb<'a>
+    where
+        A: Into<Auth>,
+    {
+        AsyncTvdb {
+            auth: auth.into(),
+            http_client: None,
+            api_base_url: DEFAULT_API_BASE_URL.into(),
+            language: "en".into(),
+            language_fallback: vec![],
+            auto_login: true,
+            jwt_token: RwLock::new(None),
+            token_lock: Mutex::new(()),
+            default_client: Arc::new(DefaultAsyncHttpClient::default()),
+        }
+    }
+
+    fn set_token(&self, token: Token) {
+        let mut j = self.jwt_token.write().unwrap_or_else(|e| e.into_inner());
+        *j = Some(token);
+    }
+
+    fn get_token(&self) -> Option<Token> {
+        self.jwt_token
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Expiry time of the JWT session token in seconds since the Unix
+    /// epoch, if logged in and the token could be decoded
+    pub fn token_expiry(&self) -> Option<u64> {
+        self.get_token().and_then(|t| t.expires())
+    }
+
+    /// Credentials used to log in
+    pub fn auth(&self) -> &Auth {
+        &self.auth
+    }
+
+    /// Whether to log in automatically, see `Tvdb::set_auto_login`.
+    /// Defaults to `true`
+    pub fn set_auto_login(&mut self, enabled: bool) {
+        self.auto_login = enabled;
+    }
+
+    /// Sets a custom client (implementation of `AsyncRequestClient`) used
+    /// to perform HTTP requests
+    pub fn set_http_client(&mut self, client: &'a dyn AsyncRequestClient) {
+        self.http_client = Some::<&'a dyn AsyncRequestClient>(client);
+    }
+
+    /// Sets the language code (e.g `de`) sent with requests, defaults to `en`
+    pub fn set_language<S>(&mut self, language: S) -> TvdbResult<()>
+    where
+        S: Into<String>,
+    {
+        let language = language.into();
+        Language::validate_code(&language)?;
+        self.language = language;
+        Ok(())
+    }
+
+    /// Language code sent with requests
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Sets languages to fall back to, in order, when the episode name,
+    /// series name or overview is missing in the requested language, see
+    /// `Tvdb::set_language_fallback`
+    pub fn set_language_fallback<I, S>(&mut self, languages: I) -> TvdbResult<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.language_fallback = language_codes(languages)?;
+        Ok(())
+    }
+
+    /// Sets the base URL of the API, defaults to `https://api.thetvdb.com`.
+    /// Fails unless given an absolute `http` or `https` URL
+    pub fn set_api_base_url<S>(&mut self, base_url: S) -> TvdbResult<()>
+    where
+        S: Into<String>,
+    {
+        self.api_base_url = parse_base_url(&base_url.into())?;
+        Ok(())
+    }
+
+    /// Base URL of the API
+    pub fn api_base_url(&self) -> &str {
+        &self.api_base_url
+    }
+
+    /// Client used to perform HTTP requests
+    fn client(&self) -> &dyn AsyncRequestClient {
+        self.http_client
+            .unwrap_or_else(|| self.default_client.as_ref())
+    }
+
+    /// Perform request, returning the body of successful responses
+    async fn send(&self, request: Request) -> TvdbResult<String> {
+        let resp = self.client().request(&request).await?;
+        check_response(&request, resp)
+    }
+
+    /// Authenticate with TheTVDB, storing the JWT token internally for use by
+    /// other methods
+    pub async fn login(&self) -> TvdbResult<bool> {
+        let result = self
+            .send(login_request(&self.api_base_url, &self.auth)?)
+            .await?;
+        self.set_token(parse_token(&result)?);
+        Ok(true)
+    }
+
+    /// Exchange the current JWT token for one with a later expiry time
+    /// <https://api.thetvdb.com/swagger#!/Authentication/get_refresh_token>
+    pub async fn refresh_token(&self) -> TvdbResult<bool> {
+        let req = Request::new(Method::Get, Endpoint::RefreshToken.url(&self.api_base_url)?);
+        let result = self
+            .send(with_token(req, self.get_token().as_ref()))
+            .await?;
+        self.set_token(parse_token(&result)?);
+        Ok(true)
+    }
+
+    /// Log in if there is no token yet or the token has expired (and
+    /// automatic login is enabled), or refresh it if it is about to expire.
+    /// Concurrent requests log in only once
+    async fn ensure_fresh_token(&self) -> TvdbResult<()> {
+        let _guard = self.token_lock.lock().await;
+
+        match TokenRenewal::of(self.get_token().as_ref(), self.auto_login) {
+            TokenRenewal::Keep => Ok(()),
+            TokenRenewal::Login => self.login().await.map(|_| ()),
+            TokenRenewal::Refresh => match self.refresh_token().await {
+                Ok(_) => Ok(()),
+                Err(_) if self.auto_login => self.login().await.map(|_| ()),
+                // The current token is still valid for now
+                Err(e) => {
+                    warn!("Failed to refresh session token: {}", e);
+                    Ok(())
+                }
+            },
+        }
+    }
+
+    /// Perform request authenticated with the JWT token, refreshing it
+    /// first if needed. If the token is rejected, logs in again and retries
+    /// once (unless automatic login is disabled)
+    async fn send_authorized(&self, request: Request) -> TvdbResult<String> {
+        self.ensure_fresh_token().await?;
+
+        let token = self.get_token();
+        let mut resp = self
+            .client()
+            .request(&with_token(request.clone(), token.as_ref()))
+            .await?;
+        if let (401, Some(rejected), true) = (resp.status, token, self.auto_login) {
+            let token = self.replace_rejected_token(&rejected).await?;
+            resp = self
+                .client()
+                .request(&with_token(request.clone(), token.as_ref()))
+                .await?;
+        }
+        check_response(&request, resp)
+    }
+
+    /// Log in again after the server rejected the given token, unless
+    /// another request already replaced it meanwhile. Returns the new token
+    async fn replace_rejected_token(&self, rejected: &Token) -> TvdbResult<Option<Token>> {
+        let _guard = self.token_lock.lock().await;
+        if self.get_token().as_ref() == Some(rejected) {
+            self.login().await?;
+        }
+        Ok(self.get_token())
+    }
+
+    /// Fetch the endpoint in the given language and parse the JSON response
+    async fn get<T>(&self, endpoint: Endpoint<'_>, language: &str) -> TvdbResult<T>
+    where
+        T: DeserializeOwned,
+    {
+        let data = self
+            .send_authorized(endpoint.get(&self.api_base_url, language)?)
+            .await?;
+        // Parse result
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Like `get`, filling translatable fields missing in `language` from
+    /// the fallback languages, see `Tvdb::set_language_fallback`
+    async fn get_translated<T>(&self, endpoint: Endpoint<'_>, language: &str) -> TvdbResult<T>
+    where
+        T: DeserializeOwned + TranslatableResult,
+    {
+        let mut result: T = self.get(endpoint, language).await?;
+        let mut fallback = Fallback::new(language, &self.language_fallback, result.items_mut());
+        while let Some(other_language) = fallback.next_language(result.items()) {
+            match self.get::<T>(endpoint, other_language).await {
+                Ok(other) => fallback.fill(result.items_mut(), other.items(), other_language),
+                Err(e) => warn!(
+                    "Failed to fetch fallback language {}: {}",
+                    other_language, e
+                ),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Perform request with the given method (e.g PUT) and parse the JSON
+    /// response
+    async fn modify<T>(&self, method: Method, endpoint: Endpoint<'_>) -> TvdbResult<T>
+    where
+        T: DeserializeOwned,
+    {
+        let req = Request::new(method, endpoint.url(&self.api_base_url)?);
+        let data = self.send_authorized(req).await?;
+        // Parse result
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Search for series by name or IMDB ID
+    /// <https://api.thetvdb.com/swagger#!/Search/get_search_series>
+    pub async fn search(
+        &self,
+        name: Option<&str>,
+        imdb_id: Option<&str>,
+    ) -> TvdbResult<SeriesSearchResult> {
+        self.get(Endpoint::Search { name, imdb_id }, &self.language)
+            .await
+    }
+
+    /// Full information about given episode
+    /// <https://api.thetvdb.com/swagger#!/Episodes/get_episodes_id>
+    pub async fn episode<E>(&self, id: E) -> TvdbResult<EpisodeRecordResult>
+    where
+        E: Into<EpisodeId>,
+    {
+        let id = id.into();
+        self.get_translated(Endpoint::Episode(id.seriesid), &id.language)
+            .await
+    }
+
+    /// Full information about given series
+    /// <https://api.thetvdb.com/swagger#!/Series/get_series_id>
+    pub async fn series<S>(&self, id: S) -> TvdbResult<SeriesRecordResult>
+    where
+        S: Into<SeriesId>,
+    {
+        self.get_translated(Endpoint::Series(id.into().seriesid), &self.language)
+            .await
+    }
+
+    /// Actors for given series
+    /// <https://api.thetvdb.com/swagger#!/Series/get_series_id_actors>
+    pub async fn actors<S>(&self, id: S) -> TvdbResult<SeriesActorsResult>
+    where
+        S: Into<SeriesId>,
+    {
+        self.get(Endpoint::Actors(id.into().seriesid), &self.language)
+            .await
+    }
+
+    /// All episodes for given series
+    pub async fn series_episodes<S>(&self, id: S, page: u32) -> TvdbResult<SeriesEpisodesResult>
+    where
+        S: Into<SeriesId>,
+    {
+        let id = id.into().seriesid;
+        self.get_translated(Endpoint::SeriesEpisodes { id, page }, &self.language)
+            .await
+    }
+
+    /// Number of seasons and episodes for given series
+    /// <https://api.thetvdb.com/swagger#!/Series/get_series_id_episodes_summary>
+    pub async fn series_episodes_summary<S>(&self, id: S) -> TvdbResult<EpisodesSummaryResult>
+    where
+        S: Into<SeriesId>,
+    {
+        self.get(
+            Endpoint::SeriesEpisodesSummary(id.into().seriesid),
+            &self.language,
+        )
+        .await
+    }
+
+    /// Number of images available for given series, by type
+    /// <https://api.thetvdb.com/swagger#!/Series/get_series_id_images>
+    pub async fn series_images<S>(&self, id: S) -> TvdbResult<SeriesImagesResult>
+    where
+        S: Into<SeriesId>,
+    {
+        self.get(Endpoint::SeriesImages(id.into().seriesid), &self.language)
+            .await
+    }
+
+    /// Images for given series matching the query
+    /// <https://api.thetvdb.com/swagger#!/Series/get_series_id_images_query>
+    pub async fn series_images_query<S>(
+        &self,
+        id: S,
+        query: &ImageQuery,
+    ) -> TvdbResult<SeriesImagesQueryResult>
+    where
+        S: Into<SeriesId>,
+    {
+        let id = id.into().seriesid;
+        self.get(Endpoint::SeriesImagesQuery { id, query }, &self.language)
+            .await
+    }
+
+    /// Episodes for given series matching the query, e.g by aired season and
+    /// episode number
+    /// <https://api.thetvdb.com/swagger#!/Series/get_series_id_episodes_query>
+    pub async fn series_episodes_query<S>(
+        &self,
+        id: S,
+        query: &EpisodeQuery,
+        page: u32,
+    ) -> TvdbResult<SeriesEpisodesResult>
+    where
+        S: Into<SeriesId>,
+    {
+        let id = id.into().seriesid;
+        self.get_translated(
+            Endpoint::SeriesEpisodesQuery { id, query, page },
+            &self.language,
+        )
+        .await
+    }
+
+    /// Full information about the episode with the given aired season and
+    /// episode number, see `Tvdb::season_episode`
+    pub async fn season_episode<S>(&self, id: S, season: u32, episode: u32) -> TvdbResult<Episode>
+    where
+        S: TryInto<SeriesId>,
+        TvdbError: From<S::Error>,
+    {
+        let query = season_episode_query(season, episode);
+        let endpoint = Endpoint::SeriesEpisodesQuery {
+            id: id.try_into()?.seriesid,
+            query: &query,
+            page: 1,
+        };
+        let epid = first_episode_id(self.get(endpoint, &self.language).await?)?;
+
+        self.episode(EpisodeId::new(epid, &self.language))
+            .await?
+            .data
+            .ok_or(TvdbError::EpisodeNotFound)
+    }
+
+    /// All languages supported by TheTVDB
+    /// <https://api.thetvdb.com/swagger#!/Languages/get_languages>
+    pub async fn languages(&self) -> TvdbResult<LanguagesResult> {
+        self.get(Endpoint::Languages, &self.language).await
+    }
+
+    /// Information about the language with given ID
+    /// <https://api.thetvdb.com/swagger#!/Languages/get_languages_id>
+    pub async fn language_by_id(&self, id: u32) -> TvdbResult<LanguageResult> {
+        self.get(Endpoint::Language(id), &self.language).await
+    }
+
+    /// Series changed between the `from` and `to` times, see
+    /// `Tvdb::updated_since`
+    /// <https://api.thetvdb.com/swagger#!/Updates/get_updated_query>
+    pub async fn updated_since(
+        &self,
+        from: u32,
+        to: Option<u32>,
+    ) -> TvdbResult<Vec<UpdatedSeries>> {
+        let mut found = vec![];
+        for (from, to) in updated_windows(from, to)? {
+            let result: UpdatedResult = self
+                .get(Endpoint::Updated { from, to }, &self.language)
+                .await?;
+            found.extend(result.data.unwrap_or_default());
+        }
+        Ok(merge_updated(found))
+    }
+
+    /// Information about the logged in user
+    /// <https://api.thetvdb.com/swagger#!/Users/get_user>
+    pub async fn user(&self) -> TvdbResult<UserResult> {
+        self.get(Endpoint::User, &self.language).await
+    }
+
+    /// IDs of the user's favorite series
+    /// <https://api.thetvdb.com/swagger#!/Users/get_user_favorites>
+    pub async fn favorites(&self) -> TvdbResult<UserFavoritesResult> {
+        self.get(Endpoint::Favorites, &self.language).await
+    }
+
+    /// Add series to the user's favorites, returning the updated favorites
+    /// <https://api.thetvdb.com/swagger#!/Users/put_user_favorites_id>
+    pub async fn add_favorite<S>(&self, id: S) -> TvdbResult<UserFavoritesResult>
+    where
+        S: Into<SeriesId>,
+    {
+        self.modify(Method::Put, Endpoint::Favorite(id.into().seriesid))
+            .await
+    }
+
+    /// Remove series from the user's favorites, returning the updated
+    /// favorites
+    /// <https://api.thetvdb.com/swagger#!/Users/delete_user_favorites_id>
+    pub async fn remove_favorite<S>(&self, id: S) -> TvdbResult<UserFavoritesResult>
+    where
+        S: Into<SeriesId>,
+    {
+        self.modify(Method::Delete, Endpoint::Favorite(id.into().seriesid))
+            .await
+    }
+
+    /// All of the user's ratings
+    /// <https://api.thetvdb.com/swagger#!/Users/get_user_ratings>
+    pub async fn ratings(&self) -> TvdbResult<UserRatingsResult> {
+        self.get(Endpoint::Ratings, &self.language).await
+    }
+
+    /// The user's ratings of the given type of item
+    /// <https://api.thetvdb.com/swagger#!/Users/get_user_ratings_query>
+    pub async fn ratings_query(&self, item_type: RatingType) -> TvdbResult<UserRatingsResult> {
+        self.get(Endpoint::RatingsQuery(item_type), &self.language)
+            .await
+    }
+
+    /// Rate the given item, replacing any existing rating
+    /// <https://api.thetvdb.com/swagger#!/Users/put_user_ratings_itemType_itemId_itemRating>
+    pub async fn add_rating(
+        &self,
+        item_type: RatingType,
+        item_id: u32,
+        rating: u32,
+    ) -> TvdbResult<UserRatingsResult> {
+        let endpoint = Endpoint::SetRating {
+            item_type,
+            item_id,
+            rating,
+        };
+        self.modify(Method::Put, endpoint).await
+    }
+
+    /// Remove the user's rating of the given item
+    /// <https://api.thetvdb.com/swagger#!/Users/delete_user_ratings_itemType_itemId>
+    pub async fn delete_rating(
+        &self,
+        item_type: RatingType,
+        item_id: u32,
+    ) -> TvdbResult<UserRatingsResult> {
+        self.modify(Method::Delete, Endpoint::Rating { item_type, item_id })
+            .await
+    }
+}
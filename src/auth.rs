@@ -0,0 +1,214 @@
+//! Authentication with TheTVDB
+
+use std::fmt::Debug;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::TvdbResult;
+
+/// How long, in seconds, before expiry a token is refreshed
+pub const TOKEN_REFRESH_MARGIN: u64 = 60 * 60;
+
+/// Credentials used to log in. The API key alone gives access to all public
+/// data, while the user name and user key (found on the account page of
+/// TheTVDB.com) are additionally required for the `/user` endpoints
+///
+/// # Examples
+/// ```
+/// let auth = tvdb::Auth::new("0629B785CE550C8D").with_user("someone", "ABCDEF0123456789");
+/// let api = tvdb::Tvdb::new(auth);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Auth {
+    /// Your API key from TheTVDB.com
+    pub apikey: String,
+    pub username: Option<String>,
+    pub userkey: Option<String>,
+}
+
+impl Auth {
+    /// Authenticate with only an API key. A key can be acquired via the
+    /// [API Key Registration page](http://thetvdb.com/?tab=apiregister)
+    pub fn new<S>(apikey: S) -> Auth
+    where
+        S: Into<String>,
+    {
+        Auth {
+            apikey: apikey.into(),
+            username: None,
+            userkey: None,
+        }
+    }
+
+    /// Additionally authenticate as the given user
+    pub fn with_user<U, K>(mut self, username: U, userkey: K) -> Auth
+    where
+        U: Into<String>,
+        K: Into<String>,
+    {
+        self.username = Some(username.into());
+        self.userkey = Some(userkey.into());
+        self
+    }
+}
+
+impl From<&str> for Auth {
+    fn from(apikey: &str) -> Auth {
+        Auth::new(apikey)
+    }
+}
+
+impl From<String> for Auth {
+    fn from(apikey: String) -> Auth {
+        Auth::new(apikey)
+    }
+}
+
+/// JWT session token, as returned by logging in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    value: String,
+    expires: Option<u64>,
+}
+
+impl Token {
+    /// Wrap the given JWT, decoding its expiry time
+    pub fn new<S>(value: S) -> Token
+    where
+        S: Into<String>,
+    {
+        let value = value.into();
+        let expires = decode_expiry(&value);
+        Token { value, expires }
+    }
+
+    /// The encoded JWT, as sent in the `Authorization` header
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Expiry time in seconds since the Unix epoch, if the token could be
+    /// decoded
+    pub fn expires(&self) -> Option<u64> {
+        self.expires
+    }
+
+    /// Whether the token expires within the given number of seconds. Tokens
+    /// with an unknown expiry time are assumed to be valid
+    pub fn expires_within(&self, secs: u64) -> bool {
+        match self.expires {
+            Some(exp) => now().saturating_add(secs) >= exp,
+            None => false,
+        }
+    }
+
+    /// Whether the token has expired
+    pub fn is_expired(&self) -> bool {
+        self.expires_within(0)
+    }
+}
+
+/// Current time in seconds since the Unix epoch
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// `exp` claim of a JWT, from the base64 encoded JSON payload
+fn decode_expiry(jwt: &str) -> Option<u64> {
+    let payload = jwt.split('.').nth(1)?;
+    let decoded =
+        base64::decode_config(payload.trim_end_matches('='), base64::URL_SAFE_NO_PAD).ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&decoded).ok()?;
+    claims["exp"].as_u64()
+}
+
+/// Persists the session token, so it can be reused by later processes
+/// instead of logging in again
+pub trait TokenStore: Debug + Send + Sync {
+    /// Previously saved token, if any
+    fn load(&self) -> TvdbResult<Option<Token>>;
+
+    /// Save the token, replacing any previous one
+    fn save(&self, token: &Token) -> TvdbResult<()>;
+}
+
+/// Stores the token in a file. Tokens are tied to the credentials used to
+/// log in, so use a separate file for each API key and user
+#[derive(Debug, Clone)]
+pub struct FileTokenStore {
+    path: PathBuf,
+}
+
+impl FileTokenStore {
+    pub fn new<P>(path: P) -> FileTokenStore
+    where
+        P: Into<PathBuf>,
+    {
+        FileTokenStore { path: path.into() }
+    }
+
+    /// File the token is stored in
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl TokenStore for FileTokenStore {
+    /// Token from the file, unless it is missing, empty or the token has
+    /// expired
+    fn load(&self) -> TvdbResult<Option<Token>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(c) => c,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let value = contents.trim();
+        if value.is_empty() {
+            return Ok(None);
+        }
+
+        let token = Token::new(value);
+        if token.is_expired() {
+            return Ok(None);
+        }
+        Ok(Some(token))
+    }
+
+    /// Write the token to a temporary file, readable only by the current
+    /// user on unix, then move it into place so readers never see a
+    /// partially written token
+    fn save(&self, token: &Token) -> TvdbResult<()> {
+        let mut temp = self.path.as_os_str().to_owned();
+        temp.push(format!(".{}.tmp", std::process::id()));
+        let temp = PathBuf::from(temp);
+
+        // Permissions only apply to new files, so don't reuse a leftover one
+        let _ = fs::remove_file(&temp);
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let written = options
+            .open(&temp)
+            .and_then(|mut f| {
+                f.write_all(token.value().as_bytes())
+                    .and_then(|_| f.sync_all())
+            })
+            .and_then(|_| fs::rename(&temp, &self.path));
+        if let Err(e) = written {
+            let _ = fs::remove_file(&temp);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
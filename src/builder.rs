@@ -0,0 +1,199 @@
+//! Configuring a `Tvdb` in one place
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::auth::Auth;
+use crate::cache::{CachePolicy, CachingClient};
+use crate::error::TvdbResult;
+use crate::rate_limit::{RateLimit, RateLimitedClient, RateLimiter};
+use crate::raw::{DefaultHttpClient, RequestClient, Tvdb};
+use crate::retry::{RetryClient, RetryPolicy};
+
+/// Builds a `Tvdb` with the given settings. Settings which aren't given keep
+/// the same defaults as `Tvdb::new`. Requests go through the response cache,
+/// then the retry policy, then the rate limit
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// let api = tvdb::TvdbBuilder::new("0629B785CE550C8D")
+///     .language("de")
+///     .timeout(Duration::from_secs(30))
+///     .retry_policy(tvdb::RetryPolicy::new(3))
+///     .cache_policy(tvdb::CachePolicy::new(Duration::from_secs(600)))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct TvdbBuilder<'a> {
+    auth: Auth,
+    language: Option<String>,
+    api_base_url: Option<String>,
+    artwork_base_url: Option<String>,
+    http_client: Option<&'a dyn RequestClient>,
+    default_http_client: DefaultHttpClient,
+    proxy: Option<String>,
+    retry_policy: RetryPolicy,
+    cache_policy: CachePolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl<'a> TvdbBuilder<'a> {
+    /// Start with the given API key, or `Auth` to also log in as a user
+    pub fn new<A>(auth: A) -> TvdbBuilder<'a>
+    where
+        A: Into<Auth>,
+    {
+        TvdbBuilder {
+            auth: auth.into(),
+            language: None,
+            api_base_url: None,
+            artwork_base_url: None,
+            http_client: None,
+            default_http_client: DefaultHttpClient::new(),
+            proxy: None,
+            retry_policy: RetryPolicy::none(),
+            cache_policy: CachePolicy::none(),
+            rate_limiter: None,
+        }
+    }
+
+    /// Log in as the given user, in addition to the API key
+    pub fn user<U, K>(mut self, username: U, userkey: K) -> TvdbBuilder<'a>
+    where
+        U: Into<String>,
+        K: Into<String>,
+    {
+        self.auth = self.auth.with_user(username, userkey);
+        self
+    }
+
+    /// Language code (e.g `de`) sent with requests, see `Tvdb::set_language`
+    pub fn language<S>(mut self, language: S) -> TvdbBuilder<'a>
+    where
+        S: Into<String>,
+    {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Base URL of the API, see `Tvdb::set_api_base_url`
+    pub fn api_base_url<S>(mut self, base_url: S) -> TvdbBuilder<'a>
+    where
+        S: Into<String>,
+    {
+        self.api_base_url = Some(base_url.into());
+        self
+    }
+
+    /// Base URL of artwork, see `Tvdb::set_artwork_base_url`
+    pub fn artwork_base_url<S>(mut self, base_url: S) -> TvdbBuilder<'a>
+    where
+        S: Into<String>,
+    {
+        self.artwork_base_url = Some(base_url.into());
+        self
+    }
+
+    /// Custom client used to perform HTTP requests, instead of the default
+    /// client. The timeout, user agent and proxy settings only apply to the
+    /// default client
+    pub fn http_client(mut self, client: &'a dyn RequestClient) -> TvdbBuilder<'a> {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Time limit for each request, see `DefaultHttpClient::timeout`
+    pub fn timeout(mut self, timeout: Duration) -> TvdbBuilder<'a> {
+        self.default_http_client = self.default_http_client.timeout(timeout);
+        self
+    }
+
+    /// Time limit for connecting, see `DefaultHttpClient::connect_timeout`
+    pub fn connect_timeout(mut self, timeout: Duration) -> TvdbBuilder<'a> {
+        self.default_http_client = self.default_http_client.connect_timeout(timeout);
+        self
+    }
+
+    /// `User-Agent` header sent with each request
+    pub fn user_agent<S>(mut self, user_agent: S) -> TvdbBuilder<'a>
+    where
+        S: Into<String>,
+    {
+        self.default_http_client = self.default_http_client.user_agent(user_agent);
+        self
+    }
+
+    /// Send requests through the proxy at the given URL, see
+    /// `DefaultHttpClient::proxy`
+    pub fn proxy<S>(mut self, url: S) -> TvdbBuilder<'a>
+    where
+        S: Into<String>,
+    {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// How failed requests are retried, defaults to `RetryPolicy::none()`
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> TvdbBuilder<'a> {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// How responses are cached, defaults to `CachePolicy::none()`
+    pub fn cache_policy(mut self, policy: CachePolicy) -> TvdbBuilder<'a> {
+        self.cache_policy = policy;
+        self
+    }
+
+    /// Limit the rate of requests, including each retry. The limit is
+    /// shared by clones of the `Tvdb`
+    pub fn rate_limit(mut self, limit: RateLimit) -> TvdbBuilder<'a> {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(limit)));
+        self
+    }
+
+    /// Limit the rate of requests with an existing limiter, e.g to share one
+    /// limit between several `Tvdb` instances
+    pub fn rate_limiter(mut self, limiter: Arc<RateLimiter>) -> TvdbBuilder<'a> {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Create the `Tvdb`, failing if the language code, API base URL or
+    /// proxy URL is invalid
+    pub fn build(self) -> TvdbResult<Tvdb<'a>> {
+        let mut api = Tvdb::new(self.auth);
+        if let Some(language) = self.language {
+            api.set_language(language)?;
+        }
+        if let Some(base_url) = self.api_base_url {
+            api.set_api_base_url(base_url)?;
+        }
+        if let Some(base_url) = self.artwork_base_url {
+            api.set_artwork_base_url(base_url);
+        }
+
+        let mut default_http_client = self.default_http_client;
+        if let Some(url) = self.proxy {
+            default_http_client = default_http_client.proxy(&url)?;
+        }
+        let default_http_client = Arc::new(default_http_client);
+        let mut client: Arc<dyn RequestClient + 'a> = match self.http_client {
+            Some(c) => Arc::new(c),
+            None => default_http_client.clone(),
+        };
+        if let Some(limiter) = self.rate_limiter {
+            client = Arc::new(RateLimitedClient::with_limiter(client, limiter));
+        }
+        let client = CachingClient::new(
+            RetryClient::new(client, self.retry_policy),
+            self.cache_policy,
+        );
+        api.set_clients(Arc::new(client), default_http_client);
+
+        Ok(api)
+    }
+}
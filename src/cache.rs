@@ -0,0 +1,145 @@
+//! In-memory caching of API responses
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::TvdbResult;
+use crate::raw::{Method, Request, RequestClient, Response};
+
+/// How long, and how many, responses are cached
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachePolicy {
+    ttl: Duration,
+    max_entries: usize,
+}
+
+impl Default for CachePolicy {
+    fn default() -> CachePolicy {
+        CachePolicy::none()
+    }
+}
+
+impl CachePolicy {
+    /// Don't cache responses
+    pub fn none() -> CachePolicy {
+        CachePolicy {
+            ttl: Duration::from_secs(0),
+            max_entries: 0,
+        }
+    }
+
+    /// Cache responses for the given time, keeping at most 1000 responses
+    pub fn new(ttl: Duration) -> CachePolicy {
+        CachePolicy {
+            ttl,
+            max_entries: 1000,
+        }
+    }
+
+    /// Sets the maximum number of cached responses. When full, the oldest
+    /// response is dropped
+    pub fn max_entries(mut self, max_entries: usize) -> CachePolicy {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// How long responses are cached
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Whether any responses are cached
+    pub fn is_enabled(&self) -> bool {
+        self.max_entries > 0 && self.ttl > Duration::from_secs(0)
+    }
+}
+
+/// Wraps a `RequestClient`, caching successful responses to GET requests
+/// according to a `CachePolicy`. Responses are cached separately for each
+/// language and session token. Any other request (e.g adding a favorite)
+/// clears the cache
+#[derive(Debug)]
+pub struct CachingClient<C> {
+    inner: C,
+    policy: CachePolicy,
+    entries: Mutex<HashMap<String, (Instant, Response)>>,
+}
+
+impl<C> CachingClient<C>
+where
+    C: RequestClient,
+{
+    pub fn new(inner: C, policy: CachePolicy) -> CachingClient<C> {
+        CachingClient {
+            inner,
+            policy,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Policy used to cache responses
+    pub fn policy(&self) -> &CachePolicy {
+        &self.policy
+    }
+
+    /// Drop all cached responses
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    fn key(request: &Request) -> String {
+        format!(
+            "{} [{}] [{}]",
+            request.url,
+            request.header_value("Accept-Language").unwrap_or(""),
+            request.header_value("Authorization").unwrap_or("")
+        )
+    }
+}
+
+impl<C> RequestClient for CachingClient<C>
+where
+    C: RequestClient,
+{
+    fn request(&self, request: &Request) -> TvdbResult<Response> {
+        if !self.policy.is_enabled() {
+            return self.inner.request(request);
+        }
+        if request.method != Method::Get {
+            self.clear();
+            return self.inner.request(request);
+        }
+
+        let key = CachingClient::<C>::key(request);
+        {
+            let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some((stored, resp)) = entries.get(&key) {
+                if stored.elapsed() < self.policy.ttl {
+                    return Ok(resp.clone());
+                }
+            }
+        }
+
+        let resp = self.inner.request(request)?;
+        if resp.is_success() {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            entries.retain(|_, (stored, _)| now.duration_since(*stored) < self.policy.ttl);
+            if entries.len() >= self.policy.max_entries && !entries.contains_key(&key) {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (stored, _))| *stored)
+                    .map(|(k, _)| k.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+            entries.insert(key, (now, resp.clone()));
+        }
+        Ok(resp)
+    }
+}
@@ -1,3 +1,9 @@
+use std::convert::TryFrom;
+use std::slice;
+
+use crate::error::{TvdbError, TvdbResult};
+
+#[derive(Debug, Clone, Copy)]
 pub struct SeriesId {
     pub seriesid: u32,
 }
@@ -8,6 +14,25 @@ impl From<u32> for SeriesId {
     }
 }
 
+/// Fails with `SeriesNotFound` if the search result has no ID
+impl TryFrom<SeriesSearchData> for SeriesId {
+    type Error = TvdbError;
+
+    fn try_from(x: SeriesSearchData) -> TvdbResult<Self> {
+        SeriesId::try_from(&x)
+    }
+}
+
+/// Fails with `SeriesNotFound` if the search result has no ID
+impl TryFrom<&SeriesSearchData> for SeriesId {
+    type Error = TvdbError;
+
+    fn try_from(x: &SeriesSearchData) -> TvdbResult<Self> {
+        let seriesid = x.id.ok_or(TvdbError::SeriesNotFound)?;
+        Ok(SeriesId { seriesid })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct EpisodeId {
     /// Series ID
@@ -20,7 +45,7 @@ impl EpisodeId {
     /// Constructor
     pub fn new(seriesid: u32, lang: &str) -> EpisodeId {
         EpisodeId {
-            seriesid: seriesid,
+            seriesid,
             language: lang.to_owned(),
         }
     }
@@ -35,10 +60,59 @@ impl From<u32> for EpisodeId {
     }
 }
 
+/// List of all `Language`s
+#[derive(Deserialize, Debug, Clone)]
+pub struct LanguagesResult {
+    pub data: Option<Vec<Language>>,
+    pub errors: Option<JSONErrors>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LanguageResult {
+    pub data: Option<Language>,
+    pub errors: Option<JSONErrors>,
+}
+
+/// Language supported by TheTVDB
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Language {
+    /// Language code, e.g `en`
+    pub abbreviation: Option<String>,
+    pub english_name: Option<String>,
+    pub id: Option<u32>,
+    /// Name of the language in the language itself
+    pub name: Option<String>,
+}
+
+impl Language {
+    /// Check the language code is of the form TheTVDB uses (two lowercase
+    /// letters, e.g `en`), returning `TvdbError::InvalidLanguage` otherwise
+    pub fn validate_code(code: &str) -> TvdbResult<()> {
+        if code.len() == 2 && code.bytes().all(|c| c.is_ascii_lowercase()) {
+            Ok(())
+        } else {
+            Err(TvdbError::InvalidLanguage {
+                language: code.into(),
+            })
+        }
+    }
+}
+
+/// https://api.thetvdb.com/swagger#/Authentication
+#[derive(Serialize, Debug)]
+pub(crate) struct LoginRequest<'a> {
+    pub(crate) apikey: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) username: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) userkey: Option<&'a str>,
+}
+
 /// https://api.thetvdb.com/swagger#/Authentication
 #[derive(Deserialize, Debug)]
-struct LoginResponse {
-    token: String,
+pub(crate) struct LoginResponse {
+    pub(crate) token: String,
 }
 
 /// List of `SeriesSearchData`, returned from a search
@@ -65,7 +139,7 @@ pub struct SeriesSearchData {
 impl From<SeriesSearchData> for EpisodeId {
     fn from(x: SeriesSearchData) -> Self {
         EpisodeId {
-            seriesid: x.id.unwrap() as u32,
+            seriesid: x.id.unwrap(),
             language: "en".into(),
         }
     }
@@ -79,6 +153,195 @@ pub struct JSONErrors {
     pub invalid_query_params: Option<Vec<String>>,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesRecordResult {
+    pub data: Option<Series>,
+    pub errors: Option<JSONErrors>,
+}
+
+/// Language each translatable field was taken from
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldLanguages {
+    pub episode_name: Option<String>,
+    pub series_name: Option<String>,
+    pub overview: Option<String>,
+}
+
+/// Result containing records with translatable fields
+pub(crate) trait TranslatableResult {
+    type Item: Translatable;
+
+    fn items(&self) -> &[Self::Item];
+
+    fn items_mut(&mut self) -> &mut [Self::Item];
+}
+
+impl TranslatableResult for SeriesRecordResult {
+    type Item = Series;
+
+    fn items(&self) -> &[Series] {
+        self.data.as_slice()
+    }
+
+    fn items_mut(&mut self) -> &mut [Series] {
+        self.data.as_mut().map_or(&mut [], slice::from_mut)
+    }
+}
+
+impl TranslatableResult for EpisodeRecordResult {
+    type Item = Episode;
+
+    fn items(&self) -> &[Episode] {
+        self.data.as_slice()
+    }
+
+    fn items_mut(&mut self) -> &mut [Episode] {
+        self.data.as_mut().map_or(&mut [], slice::from_mut)
+    }
+}
+
+impl TranslatableResult for SeriesEpisodesResult {
+    type Item = BasicEpisode;
+
+    fn items(&self) -> &[BasicEpisode] {
+        self.data.as_deref().unwrap_or_default()
+    }
+
+    fn items_mut(&mut self) -> &mut [BasicEpisode] {
+        self.data.as_deref_mut().unwrap_or_default()
+    }
+}
+
+/// Record with fields which may be missing in some languages
+pub(crate) trait Translatable {
+    /// ID used to match up the same record fetched in different languages
+    fn translation_id(&self) -> Option<u32>;
+
+    /// Whether any translatable field is missing
+    fn is_incomplete(&self) -> bool;
+
+    /// Record `language` as the source of all present fields which do not
+    /// have a language yet
+    fn mark_language(&mut self, language: &str);
+
+    /// Fill missing fields from `other`, which was fetched in `language`
+    fn fill_from(&mut self, other: &Self, language: &str);
+}
+
+fn is_missing(field: &Option<String>) -> bool {
+    field.as_ref().is_none_or(|x| x.trim().is_empty())
+}
+
+fn mark_field(field: &Option<String>, field_language: &mut Option<String>, language: &str) {
+    if !is_missing(field) && field_language.is_none() {
+        *field_language = Some(language.into());
+    }
+}
+
+fn fill_field(
+    field: &mut Option<String>,
+    field_language: &mut Option<String>,
+    other: &Option<String>,
+    language: &str,
+) {
+    if is_missing(field) && !is_missing(other) {
+        *field = other.clone();
+        *field_language = Some(language.into());
+    }
+}
+
+/// Complete info for a series
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Series {
+    pub added: Option<String>,
+    pub added_by: Option<u32>,
+    pub airs_day_of_week: Option<String>,
+    pub airs_time: Option<String>,
+    pub aliases: Option<Vec<String>>,
+    pub banner: Option<String>,
+    pub fanart: Option<String>,
+    pub first_aired: Option<String>,
+    pub genre: Option<Vec<String>>,
+    pub id: Option<u32>,
+    pub imdb_id: Option<String>,
+    /// Not part of the series record, only set when a language fallback is
+    /// configured
+    #[serde(skip)]
+    pub language: Option<FieldLanguages>,
+    pub last_updated: Option<u32>,
+    pub network: Option<String>,
+    pub network_id: Option<String>,
+    pub overview: Option<String>,
+    pub poster: Option<String>,
+    pub rating: Option<String>,
+    pub runtime: Option<String>,
+    pub series_id: Option<String>,
+    pub series_name: Option<String>,
+    pub site_rating: Option<f32>,
+    pub site_rating_count: Option<u32>,
+    pub slug: Option<String>,
+    pub status: Option<String>,
+    pub zap2it_id: Option<String>,
+}
+
+impl Translatable for Series {
+    fn translation_id(&self) -> Option<u32> {
+        self.id
+    }
+
+    fn is_incomplete(&self) -> bool {
+        is_missing(&self.series_name) || is_missing(&self.overview)
+    }
+
+    fn mark_language(&mut self, language: &str) {
+        let langs = self.language.get_or_insert_with(Default::default);
+        mark_field(&self.series_name, &mut langs.series_name, language);
+        mark_field(&self.overview, &mut langs.overview, language);
+    }
+
+    fn fill_from(&mut self, other: &Series, language: &str) {
+        let langs = self.language.get_or_insert_with(Default::default);
+        fill_field(
+            &mut self.series_name,
+            &mut langs.series_name,
+            &other.series_name,
+            language,
+        );
+        fill_field(
+            &mut self.overview,
+            &mut langs.overview,
+            &other.overview,
+            language,
+        );
+    }
+}
+
+/// List of `Actor`s for a series
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesActorsResult {
+    pub data: Option<Vec<Actor>>,
+    pub errors: Option<JSONErrors>,
+}
+
+/// Cast member of a series
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Actor {
+    pub id: Option<u32>,
+    pub image: Option<String>,
+    pub image_added: Option<String>,
+    pub image_author: Option<u32>,
+    pub last_updated: Option<String>,
+    pub name: Option<String>,
+    pub role: Option<String>,
+    pub series_id: Option<u32>,
+    pub sort_order: Option<u32>,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct EpisodeRecordResult {
@@ -102,12 +365,15 @@ pub struct Episode {
     pub dvd_discid: Option<String>,
     pub dvd_episode_number: Option<f32>,
     pub dvd_season: Option<u32>,
+    /// Empty when the episode has no name in the requested language
+    #[serde(default, deserialize_with = "deserialize_nullable_string")]
     pub episode_name: String, // FIXME: Should be optional
     pub filename: Option<String>,
     pub first_aired: Option<String>,
     pub guest_stars: Option<Vec<String>>,
     pub id: Option<u32>,
     pub imdb_id: Option<String>,
+    pub language: Option<FieldLanguages>,
     pub last_updated: Option<u32>,
     pub last_updated_by: Option<u32>, // FIXME: Should be String
     pub overview: Option<String>,
@@ -123,6 +389,46 @@ pub struct Episode {
     pub writers: Option<Vec<String>>,
 }
 
+fn deserialize_nullable_string<'de, D>(d: D) -> Result<String, D::Error>
+where
+    D: ::serde::Deserializer<'de>,
+{
+    let x: Option<String> = ::serde::Deserialize::deserialize(d)?;
+    Ok(x.unwrap_or_default())
+}
+
+impl Translatable for Episode {
+    fn translation_id(&self) -> Option<u32> {
+        self.id
+    }
+
+    fn is_incomplete(&self) -> bool {
+        self.episode_name.trim().is_empty() || is_missing(&self.overview)
+    }
+
+    fn mark_language(&mut self, language: &str) {
+        let langs = self.language.get_or_insert_with(Default::default);
+        if !self.episode_name.trim().is_empty() && langs.episode_name.is_none() {
+            langs.episode_name = Some(language.into());
+        }
+        mark_field(&self.overview, &mut langs.overview, language);
+    }
+
+    fn fill_from(&mut self, other: &Episode, language: &str) {
+        let langs = self.language.get_or_insert_with(Default::default);
+        if self.episode_name.trim().is_empty() && !other.episode_name.trim().is_empty() {
+            self.episode_name = other.episode_name.clone();
+            langs.episode_name = Some(language.into());
+        }
+        fill_field(
+            &mut self.overview,
+            &mut langs.overview,
+            &other.overview,
+            language,
+        );
+    }
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct SeriesEpisodesResult {
     pub data: Option<Vec<BasicEpisode>>,
@@ -130,6 +436,235 @@ pub struct SeriesEpisodesResult {
     pub links: Option<Links>,
 }
 
+/// Number which TheTVDB sometimes sends as a string
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrNumber {
+    Number(u32),
+    String(String),
+}
+
+impl StringOrNumber {
+    fn into_u32<E: ::serde::de::Error>(self) -> Result<u32, E> {
+        match self {
+            StringOrNumber::Number(n) => Ok(n),
+            StringOrNumber::String(s) => s.trim().parse().map_err(E::custom),
+        }
+    }
+}
+
+fn deserialize_count<'de, D>(d: D) -> Result<Option<u32>, D::Error>
+where
+    D: ::serde::Deserializer<'de>,
+{
+    let x: Option<StringOrNumber> = ::serde::Deserialize::deserialize(d)?;
+    x.map(StringOrNumber::into_u32).transpose()
+}
+
+fn deserialize_sorted_numbers<'de, D>(d: D) -> Result<Vec<u32>, D::Error>
+where
+    D: ::serde::Deserializer<'de>,
+{
+    let x: Option<Vec<StringOrNumber>> = ::serde::Deserialize::deserialize(d)?;
+    let mut numbers = x
+        .unwrap_or_default()
+        .into_iter()
+        .map(StringOrNumber::into_u32)
+        .collect::<Result<Vec<u32>, _>>()?;
+    numbers.sort_unstable();
+    Ok(numbers)
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct EpisodesSummaryResult {
+    pub data: Option<EpisodesSummary>,
+    pub errors: Option<JSONErrors>,
+}
+
+/// Number of seasons and episodes in a series
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EpisodesSummary {
+    /// Aired season numbers, sorted
+    #[serde(default, deserialize_with = "deserialize_sorted_numbers")]
+    pub aired_seasons: Vec<u32>,
+    #[serde(default, deserialize_with = "deserialize_count")]
+    pub aired_episodes: Option<u32>,
+    /// DVD season numbers, sorted
+    #[serde(default, deserialize_with = "deserialize_sorted_numbers")]
+    pub dvd_seasons: Vec<u32>,
+    #[serde(default, deserialize_with = "deserialize_count")]
+    pub dvd_episodes: Option<u32>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SeriesImagesResult {
+    pub data: Option<ImagesCounts>,
+    pub errors: Option<JSONErrors>,
+}
+
+/// Number of images available for a series, for each key type
+#[derive(Deserialize, Debug, Clone)]
+pub struct ImagesCounts {
+    pub fanart: Option<u32>,
+    pub poster: Option<u32>,
+    pub season: Option<u32>,
+    pub seasonwide: Option<u32>,
+    pub series: Option<u32>,
+}
+
+/// Kind of artwork
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageKeyType {
+    Fanart,
+    Poster,
+    Season,
+    Seasonwide,
+    Series,
+}
+
+impl ImageKeyType {
+    /// Name used for the `keyType` by TheTVDB
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ImageKeyType::Fanart => "fanart",
+            ImageKeyType::Poster => "poster",
+            ImageKeyType::Season => "season",
+            ImageKeyType::Seasonwide => "seasonwide",
+            ImageKeyType::Series => "series",
+        }
+    }
+}
+
+/// Filters for querying a series' images
+#[derive(Debug, Clone)]
+pub struct ImageQuery {
+    pub key_type: ImageKeyType,
+    /// Resolution such as `1920x1080`
+    pub resolution: Option<String>,
+    /// Sub key, such as the season number for `ImageKeyType::Season`
+    pub sub_key: Option<String>,
+}
+
+impl ImageQuery {
+    /// Query for all images of the given type
+    pub fn new(key_type: ImageKeyType) -> ImageQuery {
+        ImageQuery {
+            key_type,
+            resolution: None,
+            sub_key: None,
+        }
+    }
+
+    /// Query parameters, as expected by the `/series/{id}/images/query` endpoint
+    pub(crate) fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![("keyType", self.key_type.as_str().to_owned())];
+        if let Some(ref x) = self.resolution {
+            params.push(("resolution", x.clone()));
+        }
+        if let Some(ref x) = self.sub_key {
+            params.push(("subKey", x.clone()));
+        }
+        params
+    }
+}
+
+/// List of `Image`s matching a query
+#[derive(Deserialize, Debug, Clone)]
+pub struct SeriesImagesQueryResult {
+    pub data: Option<Vec<Image>>,
+    pub errors: Option<JSONErrors>,
+}
+
+impl SeriesImagesQueryResult {
+    /// Image with the highest average rating, if any
+    pub fn highest_rated(&self) -> Option<&Image> {
+        self.data.as_ref().and_then(|images| {
+            images
+                .iter()
+                .fold(None, |best: Option<&Image>, img| match best {
+                    Some(b) if b.average_rating() >= img.average_rating() => Some(b),
+                    _ => Some(img),
+                })
+        })
+    }
+}
+
+/// Info for a single piece of artwork
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Image {
+    /// Path relative to the artwork host
+    pub file_name: Option<String>,
+    pub id: Option<u32>,
+    pub key_type: Option<String>,
+    pub language_id: Option<u32>,
+    pub ratings_info: Option<RatingsInfo>,
+    pub resolution: Option<String>,
+    pub sub_key: Option<String>,
+    /// Path of thumbnail, relative to the artwork host
+    pub thumbnail: Option<String>,
+}
+
+impl Image {
+    /// Average rating, or 0 if unrated
+    pub fn average_rating(&self) -> f32 {
+        self.ratings_info
+            .as_ref()
+            .and_then(|r| r.average)
+            .unwrap_or(0.0)
+    }
+}
+
+/// User ratings of an image
+#[derive(Deserialize, Debug, Clone)]
+pub struct RatingsInfo {
+    pub average: Option<f32>,
+    pub count: Option<u32>,
+}
+
+/// Filters for querying a series' episodes, unset fields are not filtered on
+#[derive(Debug, Clone, Default)]
+pub struct EpisodeQuery {
+    pub absolute_number: Option<u32>,
+    pub aired_season: Option<u32>,
+    pub aired_episode: Option<u32>,
+    pub dvd_season: Option<u32>,
+    pub dvd_episode: Option<f32>,
+    pub imdb_id: Option<String>,
+    /// Date in `YYYY-MM-DD` format
+    pub first_aired: Option<String>,
+}
+
+impl EpisodeQuery {
+    /// Query parameters, as expected by the `/series/{id}/episodes/query` endpoint
+    pub(crate) fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![];
+        if let Some(x) = self.absolute_number {
+            params.push(("absoluteNumber", x.to_string()));
+        }
+        if let Some(x) = self.aired_season {
+            params.push(("airedSeason", x.to_string()));
+        }
+        if let Some(x) = self.aired_episode {
+            params.push(("airedEpisode", x.to_string()));
+        }
+        if let Some(x) = self.dvd_season {
+            params.push(("dvdSeason", x.to_string()));
+        }
+        if let Some(x) = self.dvd_episode {
+            params.push(("dvdEpisode", x.to_string()));
+        }
+        if let Some(ref x) = self.imdb_id {
+            params.push(("imdbId", x.clone()));
+        }
+        if let Some(ref x) = self.first_aired {
+            params.push(("firstAired", x.clone()));
+        }
+        params
+    }
+}
+
 /// Episode with most common attributes available
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -142,10 +677,142 @@ pub struct BasicEpisode {
     pub episode_name: Option<String>,
     pub first_aired: Option<String>,
     pub id: Option<u32>,
+    pub language: Option<FieldLanguages>,
     pub last_updated: Option<u32>,
     pub overview: Option<String>,
 }
 
+impl Translatable for BasicEpisode {
+    fn translation_id(&self) -> Option<u32> {
+        self.id
+    }
+
+    fn is_incomplete(&self) -> bool {
+        is_missing(&self.episode_name) || is_missing(&self.overview)
+    }
+
+    fn mark_language(&mut self, language: &str) {
+        let langs = self.language.get_or_insert_with(Default::default);
+        mark_field(&self.episode_name, &mut langs.episode_name, language);
+        mark_field(&self.overview, &mut langs.overview, language);
+    }
+
+    fn fill_from(&mut self, other: &BasicEpisode, language: &str) {
+        let langs = self.language.get_or_insert_with(Default::default);
+        fill_field(
+            &mut self.episode_name,
+            &mut langs.episode_name,
+            &other.episode_name,
+            language,
+        );
+        fill_field(
+            &mut self.overview,
+            &mut langs.overview,
+            &other.overview,
+            language,
+        );
+    }
+}
+
+/// List of `UpdatedSeries`, from the updated feed
+#[derive(Deserialize, Debug, Clone)]
+pub struct UpdatedResult {
+    pub data: Option<Vec<UpdatedSeries>>,
+    pub errors: Option<JSONErrors>,
+}
+
+/// Series which changed within a time range
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatedSeries {
+    pub id: u32,
+    /// Time of the last change, in seconds since the Unix epoch
+    pub last_updated: u32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct UserResult {
+    pub data: Option<User>,
+    pub errors: Option<JSONErrors>,
+}
+
+/// TheTVDB user account
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    pub favorites_displaymode: Option<String>,
+    pub id: Option<u32>,
+    pub language: Option<String>,
+    pub user_name: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct UserFavoritesResult {
+    pub data: Option<UserFavorites>,
+    pub errors: Option<JSONErrors>,
+}
+
+/// User's favorite series
+#[derive(Deserialize, Debug, Clone)]
+pub struct UserFavorites {
+    /// Series IDs
+    #[serde(default, deserialize_with = "deserialize_ids")]
+    pub favorites: Vec<u32>,
+}
+
+/// List of IDs sent as numbers or numeric strings, in their original order,
+/// ignoring the empty strings TheTVDB sends for empty lists
+fn deserialize_ids<'de, D>(d: D) -> Result<Vec<u32>, D::Error>
+where
+    D: ::serde::Deserializer<'de>,
+{
+    let x: Option<Vec<StringOrNumber>> = ::serde::Deserialize::deserialize(d)?;
+    x.unwrap_or_default()
+        .into_iter()
+        .filter(|x| match *x {
+            StringOrNumber::String(ref s) => !s.trim().is_empty(),
+            StringOrNumber::Number(_) => true,
+        })
+        .map(StringOrNumber::into_u32)
+        .collect()
+}
+
+/// Kind of item a user can rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RatingType {
+    Series,
+    Episode,
+    Image,
+}
+
+impl RatingType {
+    /// Name used for the `itemType` by TheTVDB
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            RatingType::Series => "series",
+            RatingType::Episode => "episode",
+            RatingType::Image => "image",
+        }
+    }
+}
+
+/// List of the user's `UserRating`s
+#[derive(Deserialize, Debug, Clone)]
+pub struct UserRatingsResult {
+    pub data: Option<Vec<UserRating>>,
+    pub errors: Option<JSONErrors>,
+    pub links: Option<Links>,
+}
+
+/// User's rating of a single item
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserRating {
+    pub rating: Option<f32>,
+    pub rating_item_id: Option<u32>,
+    pub rating_type: Option<String>,
+}
+
 /// Pagination links
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
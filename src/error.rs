@@ -1,7 +1,8 @@
+use std::convert::Infallible;
+use std::error::Error;
 use std::fmt;
+use std::io;
 use std::num::ParseIntError;
-use serde_json;
-use std::error::Error;
 
 /// Errors in contacting TheTVDB
 #[derive(Debug)]
@@ -12,12 +13,21 @@ pub enum TvdbError {
     /// When looking up a nonexistent series
     SeriesNotFound,
 
+    /// When looking up a nonexistent episode
+    EpisodeNotFound,
+
+    /// Language code not of the form TheTVDB accepts
+    InvalidLanguage { language: String },
+
     /// Error contacting TheTVDB.com (e.g HTTP error)
     CommunicationError { reason: String },
 
     /// Malformed data in response from TheTVDB.com
     DataError { reason: String },
 
+    /// Error reading or writing local files (e.g while downloading artwork)
+    IoError { reason: String },
+
     /// User cancelled in some interactive fashion
     Cancelled,
 }
@@ -31,10 +41,15 @@ impl fmt::Display for TvdbError {
         match *self {
             TvdbError::InternalError { reason: ref e } => write!(f, "Internal error: {}", e),
             TvdbError::SeriesNotFound => write!(f, "Series not found"),
+            TvdbError::EpisodeNotFound => write!(f, "Episode not found"),
+            TvdbError::InvalidLanguage { language: ref l } => {
+                write!(f, "Invalid language code: {:?}", l)
+            }
             TvdbError::CommunicationError { reason: ref e } => {
                 write!(f, "Communication error: {}", e)
             }
             TvdbError::DataError { reason: ref e } => write!(f, "Data error: {}", e),
+            TvdbError::IoError { reason: ref e } => write!(f, "IO error: {}", e),
             TvdbError::Cancelled => write!(f, "Cancelled"),
         }
     }
@@ -49,6 +64,13 @@ impl From<ParseIntError> for TvdbError {
     }
 }
 
+/// For conversions which can't fail, e.g `u32` to `SeriesId`
+impl From<Infallible> for TvdbError {
+    fn from(err: Infallible) -> TvdbError {
+        match err {}
+    }
+}
+
 impl From<serde_json::Error> for TvdbError {
     fn from(err: serde_json::Error) -> TvdbError {
         TvdbError::DataError {
@@ -57,6 +79,14 @@ impl From<serde_json::Error> for TvdbError {
     }
 }
 
+impl From<io::Error> for TvdbError {
+    fn from(err: io::Error) -> TvdbError {
+        TvdbError::IoError {
+            reason: format!("{}", err),
+        }
+    }
+}
+
 impl Error for TvdbError {
     fn description(&self) -> &str {
         "TvdbError"
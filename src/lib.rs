@@ -3,6 +3,7 @@
 //! [apidoc]: https://api.thetvdb.com/swagger
 //! [tvdb]: http://thetvdb.com
 
+#[macro_use]
 extern crate log;
 
 extern crate reqwest;
@@ -13,16 +14,38 @@ extern crate serde;
 extern crate serde_derive;
 extern crate serde_json;
 
+extern crate base64;
+extern crate httpdate;
+
 // Module structure
-pub mod raw;
+pub mod artwork;
+#[cfg(feature = "async")]
+pub mod async_client;
+pub mod auth;
+pub mod builder;
+pub mod cache;
 pub mod data;
 mod error;
+pub mod rate_limit;
+pub mod raw;
+pub mod retry;
+pub mod sync;
 
 // Main public API
-pub use raw::{RequestClient, Tvdb};
+pub use crate::auth::{Auth, FileTokenStore, Token, TokenStore};
+pub use crate::builder::TvdbBuilder;
+pub use crate::raw::{DefaultHttpClient, Method, Request, RequestClient, Response, Tvdb};
+
+// Request handling policies
+pub use crate::cache::CachePolicy;
+pub use crate::rate_limit::RateLimit;
+pub use crate::retry::RetryPolicy;
 
 // Expose error types
-pub use error::{TvdbError, TvdbResult};
+pub use crate::error::{TvdbError, TvdbResult};
+
+// Artwork helpers
+pub use crate::artwork::{ArtworkHost, ArtworkSize, DownloadClient, DownloadOutcome};
 
 // Expose data types
-pub use data::EpisodeId;
+pub use crate::data::{EpisodeId, EpisodeQuery, ImageKeyType, ImageQuery, RatingType};
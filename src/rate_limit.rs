@@ -0,0 +1,155 @@
+//! Client-side rate limiting of requests
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::{TvdbError, TvdbResult};
+use crate::raw::{Request, RequestClient, Response};
+
+/// Number of requests allowed per second, and how many may be made at once
+/// after a quiet period
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimit {
+    requests_per_second: f64,
+    burst: u32,
+}
+
+impl RateLimit {
+    /// Allow the given number of requests per second on average, with a
+    /// burst of one second's worth of requests. Fails unless
+    /// `requests_per_second` is a positive, finite number
+    pub fn new(requests_per_second: f64) -> TvdbResult<RateLimit> {
+        if !(requests_per_second > 0.0 && requests_per_second.is_finite()) {
+            return Err(TvdbError::InternalError {
+                reason: format!(
+                    "Requests per second must be positive, got {}",
+                    requests_per_second
+                ),
+            });
+        }
+        Ok(RateLimit {
+            requests_per_second,
+            burst: requests_per_second.ceil().max(1.0) as u32,
+        })
+    }
+
+    /// Sets the number of requests which can be made without waiting
+    pub fn burst(mut self, burst: u32) -> RateLimit {
+        self.burst = burst.max(1);
+        self
+    }
+
+    pub fn requests_per_second(&self) -> f64 {
+        self.requests_per_second
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket shared by all requests going through it. Each request takes
+/// a token, and tokens are added at the configured rate up to the burst
+/// size. When no token is left, requests wait for the next one, in the order
+/// they arrived
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    /// Limiter with a full bucket
+    pub fn new(limit: RateLimit) -> RateLimiter {
+        RateLimiter {
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(limit.burst),
+                updated: Instant::now(),
+            }),
+            limit,
+        }
+    }
+
+    pub fn limit(&self) -> &RateLimit {
+        &self.limit
+    }
+
+    /// Take a token, returning how long to wait before the request may be
+    /// made
+    pub fn reserve(&self) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.limit.requests_per_second)
+            .min(f64::from(self.limit.burst));
+        bucket.updated = now;
+
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::from_secs(0)
+        } else {
+            // Very low rates can give waits too long to represent
+            Duration::try_from_secs_f64(-bucket.tokens / self.limit.requests_per_second)
+                .unwrap_or(Duration::MAX)
+        }
+    }
+
+    /// Take a token, waiting until the request may be made. Returns the time
+    /// waited
+    pub fn acquire(&self) -> Duration {
+        let wait = self.reserve();
+        if wait > Duration::from_secs(0) {
+            thread::sleep(wait);
+        }
+        wait
+    }
+}
+
+/// Wraps a `RequestClient`, limiting the rate of requests with a
+/// `RateLimiter`. Clones of a `Tvdb` share its client, and so the rate
+/// limit. To share one limit between separately created clients, use
+/// `with_limiter` with the same limiter
+#[derive(Debug)]
+pub struct RateLimitedClient<C> {
+    inner: C,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<C> RateLimitedClient<C>
+where
+    C: RequestClient,
+{
+    pub fn new(inner: C, limit: RateLimit) -> RateLimitedClient<C> {
+        RateLimitedClient::with_limiter(inner, Arc::new(RateLimiter::new(limit)))
+    }
+
+    /// Limit requests with an existing limiter, e.g one shared with other
+    /// clients
+    pub fn with_limiter(inner: C, limiter: Arc<RateLimiter>) -> RateLimitedClient<C> {
+        RateLimitedClient { inner, limiter }
+    }
+
+    pub fn limiter(&self) -> &Arc<RateLimiter> {
+        &self.limiter
+    }
+}
+
+impl<C> RequestClient for RateLimitedClient<C>
+where
+    C: RequestClient,
+{
+    fn request(&self, request: &Request) -> TvdbResult<Response> {
+        let wait = self.limiter.reserve();
+        if wait > Duration::from_secs(0) {
+            debug!(
+                "Rate limit reached, waiting {:?} before requesting {}",
+                wait, request.url
+            );
+            thread::sleep(wait);
+        }
+        self.inner.request(request)
+    }
+}
@@ -1,181 +1,1069 @@
-/// Provides mostly direct binding to the HTTP API
+//! Provides mostly direct binding to the HTTP API
 
-use std::cell::RefCell;
+use std::borrow::Borrow;
 use std::collections::HashMap;
+use std::convert::TryInto;
 use std::fmt::Debug;
 use std::io::Read;
-use std::rc::Rc;
+use std::slice;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use reqwest;
-use serde_json;
-use url;
+use serde::de::DeserializeOwned;
 
+use super::artwork::{ArtworkHost, DownloadClient};
+use super::auth::{Auth, Token, TokenStore, TOKEN_REFRESH_MARGIN};
+use super::builder::TvdbBuilder;
 use super::data::*;
 use super::error::{TvdbError, TvdbResult};
-use data::{EpisodeId, SeriesId};
+use crate::data::{EpisodeId, SeriesId};
 
-/// Trait for custom implementations of URL fetching
-pub trait RequestClient: Debug {
-    fn get_url(&self, url: &str, jwt_token: Option<String>) -> TvdbResult<String>;
+/// Default base URL of the API, without trailing slash
+pub const DEFAULT_API_BASE_URL: &str = "https://api.thetvdb.com";
+
+/// Longest time range, in seconds, the updated feed accepts in one request
+pub const UPDATED_MAX_RANGE: u32 = 7 * 24 * 60 * 60;
+
+/// HTTP method of a `Request`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Delete,
 }
 
-/// Default implementation of RequestClient
-#[derive(Debug)]
-pub struct DefaultHttpClient;
+impl Method {
+    /// Name of the method, e.g `GET`
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Delete => "DELETE",
+        }
+    }
+}
+
+/// HTTP request to be performed by a `RequestClient`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: Method,
+    pub url: String,
+    /// Header names and values, such as `Authorization` and
+    /// `Accept-Language`
+    pub headers: Vec<(String, String)>,
+    /// Request body, JSON encoded
+    pub body: Option<String>,
+}
+
+impl Request {
+    /// Request with no headers or body
+    pub fn new<S>(method: Method, url: S) -> Request
+    where
+        S: Into<String>,
+    {
+        Request {
+            method,
+            url: url.into(),
+            headers: vec![],
+            body: None,
+        }
+    }
+
+    /// Add a header
+    pub fn header<N, V>(mut self, name: N, value: V) -> Request
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set a JSON body, along with the matching `Content-Type` header
+    pub fn json_body<S>(self, body: S) -> Request
+    where
+        S: Into<String>,
+    {
+        let mut req = self.header("Content-Type", "application/json");
+        req.body = Some(body.into());
+        req
+    }
+
+    /// Value of the first header with the given (case insensitive) name
+    pub fn header_value(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/// HTTP response returned by a `RequestClient`. Unsuccessful status codes
+/// should be returned as a response rather than an error, so they can be
+/// handled by `Tvdb`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    /// Response with no headers
+    pub fn new<S>(status: u16, body: S) -> Response
+    where
+        S: Into<String>,
+    {
+        Response {
+            status,
+            headers: vec![],
+            body: body.into(),
+        }
+    }
+
+    /// Whether the status code is in the 2xx range
+    pub fn is_success(&self) -> bool {
+        self.status >= 200 && self.status < 300
+    }
+
+    /// Value of the first header with the given (case insensitive) name
+    pub fn header_value(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+fn find_header<'h>(headers: &'h [(String, String)], name: &str) -> Option<&'h str> {
+    headers
+        .iter()
+        .find(|h| h.0.eq_ignore_ascii_case(name))
+        .map(|h| h.1.as_str())
+}
+
+/// Trait for custom implementations of performing HTTP requests. Clients
+/// must be thread-safe, so a `Tvdb` can be shared between threads
+pub trait RequestClient: Debug + Send + Sync {
+    fn request(&self, request: &Request) -> TvdbResult<Response>;
+}
+
+impl<C> RequestClient for &C
+where
+    C: RequestClient + ?Sized,
+{
+    fn request(&self, request: &Request) -> TvdbResult<Response> {
+        (**self).request(request)
+    }
+}
+
+impl<C> RequestClient for Arc<C>
+where
+    C: RequestClient + ?Sized,
+{
+    fn request(&self, request: &Request) -> TvdbResult<Response> {
+        (**self).request(request)
+    }
+}
+
+/// User agent sent by `DefaultHttpClient` unless changed
+pub const DEFAULT_USER_AGENT: &str = concat!("tvdb-rs/", env!("CARGO_PKG_VERSION"));
+
+/// Time limit for each request made by `DefaultHttpClient` unless changed
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default implementation of RequestClient, using `reqwest`. A single
+/// connection pool is created on first use and shared by all requests (and
+/// clones of the client), so connections are kept alive between requests
+#[derive(Debug, Clone)]
+pub struct DefaultHttpClient {
+    timeout: Duration,
+    connect_timeout: Option<Duration>,
+    user_agent: String,
+    proxy: Option<reqwest::Proxy>,
+    client: OnceLock<reqwest::blocking::Client>,
+}
+
+impl Default for DefaultHttpClient {
+    fn default() -> DefaultHttpClient {
+        DefaultHttpClient::new()
+    }
+}
+
+impl DefaultHttpClient {
+    /// Client giving up on requests after `DEFAULT_TIMEOUT`, without a
+    /// separate connect timeout, sending `DEFAULT_USER_AGENT`
+    pub fn new() -> DefaultHttpClient {
+        DefaultHttpClient {
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: None,
+            user_agent: DEFAULT_USER_AGENT.into(),
+            proxy: None,
+            client: OnceLock::new(),
+        }
+    }
+
+    /// Time limit for each request, from connecting until the response body
+    /// has been read. Defaults to `DEFAULT_TIMEOUT`
+    pub fn timeout(mut self, timeout: Duration) -> DefaultHttpClient {
+        self.timeout = timeout;
+        self.client = OnceLock::new();
+        self
+    }
+
+    /// Time limit for connecting to the server
+    pub fn connect_timeout(mut self, timeout: Duration) -> DefaultHttpClient {
+        self.connect_timeout = Some(timeout);
+        self.client = OnceLock::new();
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with each request
+    pub fn user_agent<S>(mut self, user_agent: S) -> DefaultHttpClient
+    where
+        S: Into<String>,
+    {
+        self.user_agent = user_agent.into();
+        self.client = OnceLock::new();
+        self
+    }
+
+    /// Send all requests through the proxy at the given URL, e.g
+    /// `http://proxy.example.com:3128`. Without a proxy, the `HTTP_PROXY`
+    /// etc environment variables are used
+    pub fn proxy(mut self, url: &str) -> TvdbResult<DefaultHttpClient> {
+        let proxy = reqwest::Proxy::all(url).map_err(|x| TvdbError::InternalError {
+            reason: format!("Invalid proxy URL {}: {}", url, x),
+        })?;
+        self.proxy = Some(proxy);
+        self.client = OnceLock::new();
+        Ok(self)
+    }
+
+    /// Pooled `reqwest` client with the configured settings, created on
+    /// first use
+    pub(crate) fn client(&self) -> TvdbResult<&reqwest::blocking::Client> {
+        if let Some(client) = self.client.get() {
+            return Ok(client);
+        }
+
+        let mut builder = reqwest::blocking::Client::builder()
+            .user_agent(self.user_agent.as_str())
+            .timeout(self.timeout);
+        if let Some(t) = self.connect_timeout {
+            builder = builder.connect_timeout(t);
+        }
+        if let Some(ref proxy) = self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        let client = builder.build().map_err(|x| TvdbError::CommunicationError {
+            reason: format!("Error creating HTTP client: {}", x),
+        })?;
+
+        // Another thread may have created the client meanwhile, in which
+        // case that one is used
+        Ok(self.client.get_or_init(|| client))
+    }
+}
 
 impl RequestClient for DefaultHttpClient {
-    fn get_url(&self, url: &str, jwt_token: Option<String>) -> TvdbResult<String> {
-        // Make request
-        let client = reqwest::blocking::Client::new();
-        let mut req = client.get(url);
+    fn request(&self, request: &Request) -> TvdbResult<Response> {
+        let method = match request.method {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+            Method::Put => reqwest::Method::PUT,
+            Method::Delete => reqwest::Method::DELETE,
+        };
 
-        // Add auth header
-        if let Some(tok) = jwt_token {
-            req = req.bearer_auth(tok);
+        // Make request
+        let client = self.client()?;
+        let mut req = client.request(method, &request.url);
+        for (name, value) in &request.headers {
+            req = req.header(name.as_str(), value.as_str());
+        }
+        if let Some(ref body) = request.body {
+            req = req.body(body.clone());
         }
 
         // Send request
-        let mut resp = req.send().map_err(|x| {
-            TvdbError::CommunicationError {
-                reason: format!("Error creating HTTP request: {}", x),
-            }
+        let mut resp = req.send().map_err(|x| TvdbError::CommunicationError {
+            reason: format!("Error creating HTTP request: {}", x),
         })?;
 
-        // Check response
-        if !resp.status().is_success() {
-            return Err(TvdbError::CommunicationError {
-                reason: format!(
-                    "Unsuccessful HTTP response from url {}: {}",
-                    url,
-                    resp.status()
-                ),
-            });
-        }
+        let headers = resp
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.as_str().to_owned(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
 
-        let mut result = String::new();
-        resp.read_to_string(&mut result)
+        let mut body = String::new();
+        resp.read_to_string(&mut body)
             .map_err(|x| TvdbError::CommunicationError {
                 reason: format!("Error reading response: {}", x),
             })?;
-        return Ok(result);
+
+        Ok(Response {
+            status: resp.status().as_u16(),
+            headers,
+            body,
+        })
     }
 }
 
-/// Main interface
-#[derive(Debug, Clone)]
+/// Request to log in with the given credentials, to the API at `base_url`
+pub(crate) fn login_request(base_url: &str, auth: &Auth) -> TvdbResult<Request> {
+    let body = serde_json::to_string(&LoginRequest {
+        apikey: &auth.apikey,
+        username: auth.username.as_deref(),
+        userkey: auth.userkey.as_deref(),
+    })?;
+    let url = format!("{}/login", base_url);
+    Ok(Request::new(Method::Post, url).json_body(body))
+}
+
+/// The given base URL without trailing slash, failing unless it is an
+/// absolute `http` or `https` URL
+pub(crate) fn parse_base_url(base_url: &str) -> TvdbResult<String> {
+    let invalid = |reason: String| TvdbError::InternalError {
+        reason: format!("Invalid API base URL {}: {}", base_url, reason),
+    };
+    let url = url::Url::parse(base_url).map_err(|e| invalid(e.to_string()))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(invalid("not an http or https URL".into()));
+    }
+    Ok(base_url.trim_end_matches('/').to_owned())
+}
+
+/// The URL with the given query parameters added
+pub(crate) fn url_with_params<I, K, V>(url: &str, params: I) -> TvdbResult<String>
+where
+    I: IntoIterator,
+    I::Item: Borrow<(K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    url::Url::parse_with_params(url, params)
+        .map(|u| u.as_str().into())
+        .map_err(|e| TvdbError::InternalError {
+            reason: format!("Invalid URL {}: {}", url, e),
+        })
+}
+
+/// Add the given JWT token, if any, to the request
+pub(crate) fn with_token(request: Request, token: Option<&Token>) -> Request {
+    match token {
+        Some(tok) => request.header("Authorization", format!("Bearer {}", tok.value())),
+        None => request,
+    }
+}
+
+/// Body of successful responses, or an error
+pub(crate) fn check_response(request: &Request, resp: Response) -> TvdbResult<String> {
+    if !resp.is_success() {
+        return Err(TvdbError::CommunicationError {
+            reason: format!(
+                "Unsuccessful HTTP response from url {}: {}",
+                request.url, resp.status
+            ),
+        });
+    }
+
+    Ok(resp.body)
+}
+
+/// Split the range between `from` and `to` (defaulting to now) into
+/// windows the updated feed accepts
+pub(crate) fn updated_windows(from: u32, to: Option<u32>) -> TvdbResult<Vec<(u32, u32)>> {
+    let to = match to {
+        Some(t) => t,
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as u32)
+            .map_err(|e| TvdbError::InternalError {
+                reason: format!("System time before Unix epoch: {}", e),
+            })?,
+    };
+
+    let mut windows = vec![];
+    let mut start = from;
+    while start < to {
+        let end = to.min(start.saturating_add(UPDATED_MAX_RANGE));
+        windows.push((start, end));
+        start = end;
+    }
+    Ok(windows)
+}
+
+/// Each series once with its latest update time, ordered by update time
+pub(crate) fn merge_updated(found: Vec<UpdatedSeries>) -> Vec<UpdatedSeries> {
+    let mut latest: HashMap<u32, u32> = HashMap::new();
+    for s in found {
+        let t = latest.entry(s.id).or_insert(s.last_updated);
+        *t = (*t).max(s.last_updated);
+    }
+
+    let mut updated: Vec<UpdatedSeries> = latest
+        .into_iter()
+        .map(|(id, last_updated)| UpdatedSeries { id, last_updated })
+        .collect();
+    updated.sort_by_key(|s| (s.last_updated, s.id));
+    updated
+}
+
+/// API endpoints, shared by `Tvdb` and `AsyncTvdb` so both clients build
+/// the same requests and only differ in how they are sent
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Endpoint<'q> {
+    RefreshToken,
+    Search {
+        name: Option<&'q str>,
+        imdb_id: Option<&'q str>,
+    },
+    Episode(u32),
+    Series(u32),
+    Actors(u32),
+    SeriesEpisodes {
+        id: u32,
+        page: u32,
+    },
+    SeriesEpisodesQuery {
+        id: u32,
+        query: &'q EpisodeQuery,
+        page: u32,
+    },
+    SeriesEpisodesSummary(u32),
+    SeriesImages(u32),
+    SeriesImagesQuery {
+        id: u32,
+        query: &'q ImageQuery,
+    },
+    Languages,
+    Language(u32),
+    Updated {
+        from: u32,
+        to: u32,
+    },
+    User,
+    Favorites,
+    Favorite(u32),
+    Ratings,
+    RatingsQuery(RatingType),
+    Rating {
+        item_type: RatingType,
+        item_id: u32,
+    },
+    SetRating {
+        item_type: RatingType,
+        item_id: u32,
+        rating: u32,
+    },
+}
+
+impl<'q> Endpoint<'q> {
+    /// Full URL of the endpoint on the API at `base_url`
+    pub(crate) fn url(&self, base_url: &str) -> TvdbResult<String> {
+        let path = match *self {
+            Endpoint::RefreshToken => "/refresh_token".to_owned(),
+            Endpoint::Search { name, imdb_id } => {
+                let mut params = vec![];
+                if let Some(n) = name {
+                    params.push(("name", n));
+                }
+                if let Some(i) = imdb_id {
+                    params.push(("imdbId", i));
+                }
+                return url_with_params(&format!("{}/search/series", base_url), params);
+            }
+            Endpoint::Episode(id) => format!("/episodes/{id}", id = id),
+            Endpoint::Series(id) => format!("/series/{id}", id = id),
+            Endpoint::Actors(id) => format!("/series/{id}/actors", id = id),
+            Endpoint::SeriesEpisodes { id, page } => {
+                format!("/series/{id}/episodes?page={page}", id = id, page = page)
+            }
+            Endpoint::SeriesEpisodesQuery { id, query, page } => {
+                let mut params = query.params();
+                params.push(("page", page.to_string()));
+                let url = format!("{}/series/{id}/episodes/query", base_url, id = id);
+                return url_with_params(&url, params);
+            }
+            Endpoint::SeriesEpisodesSummary(id) => {
+                format!("/series/{id}/episodes/summary", id = id)
+            }
+            Endpoint::SeriesImages(id) => format!("/series/{id}/images", id = id),
+            Endpoint::SeriesImagesQuery { id, query } => {
+                let url = format!("{}/series/{id}/images/query", base_url, id = id);
+                return url_with_params(&url, query.params());
+            }
+            Endpoint::Languages => "/languages".to_owned(),
+            Endpoint::Language(id) => format!("/languages/{id}", id = id),
+            Endpoint::Updated { from, to } => format!(
+                "/updated/query?fromTime={from}&toTime={to}",
+                from = from,
+                to = to
+            ),
+            Endpoint::User => "/user".to_owned(),
+            Endpoint::Favorites => "/user/favorites".to_owned(),
+            Endpoint::Favorite(id) => format!("/user/favorites/{id}", id = id),
+            Endpoint::Ratings => "/user/ratings".to_owned(),
+            Endpoint::RatingsQuery(item_type) => format!(
+                "/user/ratings/query?itemType={item_type}",
+                item_type = item_type.as_str()
+            ),
+            Endpoint::Rating { item_type, item_id } => format!(
+                "/user/ratings/{item_type}/{item_id}",
+                item_type = item_type.as_str(),
+                item_id = item_id
+            ),
+            Endpoint::SetRating {
+                item_type,
+                item_id,
+                rating,
+            } => format!(
+                "/user/ratings/{item_type}/{item_id}/{rating}",
+                item_type = item_type.as_str(),
+                item_id = item_id,
+                rating = rating
+            ),
+        };
+        Ok(format!("{}{}", base_url, path))
+    }
+
+    /// GET request for the endpoint, in the given language
+    pub(crate) fn get(&self, base_url: &str, language: &str) -> TvdbResult<Request> {
+        Language::validate_code(language)?;
+        let req = Request::new(Method::Get, self.url(base_url)?);
+        Ok(req.header("Accept-Language", language))
+    }
+}
+
+/// Query for the episode with the given aired season and episode number
+pub(crate) fn season_episode_query(season: u32, episode: u32) -> EpisodeQuery {
+    EpisodeQuery {
+        aired_season: Some(season),
+        aired_episode: Some(episode),
+        ..Default::default()
+    }
+}
+
+/// ID of the first episode found by `season_episode_query`
+pub(crate) fn first_episode_id(found: SeriesEpisodesResult) -> TvdbResult<u32> {
+    found
+        .data
+        .and_then(|eps| eps.into_iter().next())
+        .and_then(|ep| ep.id)
+        .ok_or(TvdbError::EpisodeNotFound)
+}
+
+/// Validated language codes
+pub(crate) fn language_codes<I, S>(languages: I) -> TvdbResult<Vec<String>>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let languages: Vec<String> = languages.into_iter().map(Into::into).collect();
+    for l in &languages {
+        Language::validate_code(l)?;
+    }
+    Ok(languages)
+}
+
+/// Token from the body of a login or refresh response
+pub(crate) fn parse_token(body: &str) -> TvdbResult<Token> {
+    let deserialized: LoginResponse = serde_json::from_str(body)?;
+    Ok(Token::new(deserialized.token))
+}
+
+/// What to do with the session token before making a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenRenewal {
+    /// Use the current token, if any
+    Keep,
+    /// Log in, as there is no token yet or it has expired
+    Login,
+    /// Refresh the token as it is about to expire, logging in if that fails
+    /// (and automatic login is enabled)
+    Refresh,
+}
+
+impl TokenRenewal {
+    /// Renewal needed for the current token, if any. Without automatic
+    /// login, a missing or expired token is kept as it is
+    pub(crate) fn of(token: Option<&Token>, auto_login: bool) -> TokenRenewal {
+        match token {
+            None if auto_login => TokenRenewal::Login,
+            None => TokenRenewal::Keep,
+            Some(t) if t.is_expired() && auto_login => TokenRenewal::Login,
+            Some(t) if t.is_expired() => TokenRenewal::Keep,
+            Some(t) if t.expires_within(TOKEN_REFRESH_MARGIN) => TokenRenewal::Refresh,
+            Some(_) => TokenRenewal::Keep,
+        }
+    }
+}
+
+/// Fills fields missing from results with those fetched in the fallback
+/// languages, see `Tvdb::set_language_fallback`. The caller fetches each
+/// language returned by `next_language` and passes the result to `fill`
+pub(crate) struct Fallback<'l> {
+    language: &'l str,
+    remaining: slice::Iter<'l, String>,
+}
+
+impl<'l> Fallback<'l> {
+    /// Start filling `items`, which were fetched in `language`
+    pub(crate) fn new<T>(language: &'l str, fallback: &'l [String], items: &mut [T]) -> Fallback<'l>
+    where
+        T: Translatable,
+    {
+        if !fallback.is_empty() {
+            for item in items.iter_mut() {
+                item.mark_language(language);
+            }
+        }
+        Fallback {
+            language,
+            remaining: fallback.iter(),
+        }
+    }
+
+    /// Next language to fetch, or `None` once `items` are complete or no
+    /// fallback languages are left
+    pub(crate) fn next_language<T>(&mut self, items: &[T]) -> Option<&'l str>
+    where
+        T: Translatable,
+    {
+        if !items.iter().any(Translatable::is_incomplete) {
+            return None;
+        }
+        let language = self.language;
+        self.remaining
+            .by_ref()
+            .map(String::as_str)
+            .find(|l| *l != language)
+    }
+
+    /// Fill missing fields of `items` from `others`, fetched in `language`
+    pub(crate) fn fill<T>(&self, items: &mut [T], others: &[T], language: &str)
+    where
+        T: Translatable,
+    {
+        for (idx, item) in items.iter_mut().enumerate() {
+            if !item.is_incomplete() {
+                continue;
+            }
+            let other = match item.translation_id() {
+                Some(id) => others.iter().find(|o| o.translation_id() == Some(id)),
+                None => others.get(idx),
+            };
+            if let Some(other) = other {
+                item.fill_from(other, language);
+            }
+        }
+    }
+}
+
+/// Main interface. `Tvdb` is `Send + Sync`, so one logged in instance can be
+/// shared between threads, e.g in an `Arc`
+#[derive(Debug)]
 pub struct Tvdb<'a> {
-    /// Your API key from TheTVDB.com
-    pub key: String,
-    http_client: Option<&'a dyn RequestClient>,
-    jwt_token: RefCell<Option<String>>,
-    default_client: Rc<dyn RequestClient>,
+    auth: Auth,
+    http_client: Arc<dyn RequestClient + 'a>,
+    default_http_client: Arc<DefaultHttpClient>,
+    download_client: Option<&'a dyn DownloadClient>,
+    token_store: Option<&'a dyn TokenStore>,
+    api_base_url: String,
+    artwork_host: ArtworkHost,
+    language: String,
+    language_fallback: Vec<String>,
+    auto_login: bool,
+    jwt_token: RwLock<Option<Token>>,
+    token_lock: Mutex<()>,
+}
+
+impl<'a> Clone for Tvdb<'a> {
+    fn clone(&self) -> Tvdb<'a> {
+        Tvdb {
+            auth: self.auth.clone(),
+            http_client: self.http_client.clone(),
+            default_http_client: self.default_http_client.clone(),
+            download_client: self.download_client,
+            token_store: self.token_store,
+            api_base_url: self.api_base_url.clone(),
+            artwork_host: self.artwork_host.clone(),
+            language: self.language.clone(),
+            language_fallback: self.language_fallback.clone(),
+            auto_login: self.auto_login,
+            jwt_token: RwLock::new(self.get_token()),
+            token_lock: Mutex::new(()),
+        }
+    }
 }
 
 impl<'a> Tvdb<'a> {
-    /// Initalise API with the given API key. A key can be acquired via
-    /// the [API Key Registration page](http://thetvdb.com/?tab=apiregister)
-    pub fn new<S>(key: S) -> Tvdb<'a>
+    /// Start configuring a `Tvdb` with the given API key, or `Auth`
+    pub fn builder<A>(auth: A) -> TvdbBuilder<'a>
     where
-        S: Into<String>,
+        A: Into<Auth>,
+    {
+        TvdbBuilder::new(auth)
+    }
+
+    /// Initalise API with the given API key, or `Auth` to also log in as a
+    /// user. A key can be acquired via the
+    /// [API Key Registration page](http://thetvdb.com/?tab=apiregister)
+    pub fn new<A>(auth: A) -> Tvdb<'a>
+    where
+        A: Into<Auth>,
     {
+        let default_http_client = Arc::new(DefaultHttpClient::new());
         Tvdb {
-            key: key.into(),
-            http_client: None,
-            jwt_token: RefCell::new(None),
-            default_client: Rc::new(DefaultHttpClient {}),
+            auth: auth.into(),
+            http_client: default_http_client.clone(),
+            default_http_client,
+            download_client: None,
+            token_store: None,
+            api_base_url: DEFAULT_API_BASE_URL.into(),
+            artwork_host: ArtworkHost::default(),
+            language: "en".into(),
+            language_fallback: vec![],
+            auto_login: true,
+            jwt_token: RwLock::new(None),
+            token_lock: Mutex::new(()),
         }
     }
 
-    /// Set the JWT session token
-    fn set_token(&self, token: String) {
-        let mut j = self.jwt_token.borrow_mut();
-        *j = Some(token);
+    /// Set the JWT session token, saving it to the token store if any.
+    /// Failing to save doesn't stop the token being used by this instance
+    fn set_token(&self, token: Token) {
+        *self.jwt_token.write().unwrap_or_else(|e| e.into_inner()) = Some(token.clone());
+        if let Some(store) = self.token_store {
+            if let Err(e) = store.save(&token) {
+                warn!("Failed to save session token: {}", e);
+            }
+        }
     }
 
     /// Get JWT session token (typically set via `login` method)
-    fn get_token(&self) -> Option<String> {
-        let j = self.jwt_token.borrow();
-        match *j {
-            None => None,
-            Some(ref t) => Some(format!("{}", *t)),
-        }
+    fn get_token(&self) -> Option<Token> {
+        self.jwt_token
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Held while checking and renewing the token, so concurrent requests
+    /// log in only once
+    fn lock_token(&self) -> MutexGuard<'_, ()> {
+        self.token_lock.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Expiry time of the JWT session token in seconds since the Unix
+    /// epoch, if logged in and the token could be decoded
+    pub fn token_expiry(&self) -> Option<u64> {
+        self.get_token().and_then(|t| t.expires())
     }
 
     /// Authenticate with TheTVDB, storing the JWT token internally for use by
-    /// other methods.
+    /// other methods. Unless disabled with `set_auto_login`, this happens
+    /// automatically on the first request
     pub fn login(&self) -> TvdbResult<bool> {
-        let mut map = HashMap::new();
-        map.insert("apikey", &self.key);
+        let result = self.send(login_request(&self.api_base_url, &self.auth)?)?;
+        self.set_token(parse_token(&result)?);
+        Ok(true)
+    }
 
-        let c = reqwest::blocking::Client::new();
-        let mut resp = c.post("https://api.thetvdb.com/login")
-            .json(&map)
-            .send()
-            .map_err(|x| TvdbError::CommunicationError {
-                reason: format!("{}", x),
-            })?;
-        let mut result = String::new();
-        resp.read_to_string(&mut result)
-            .map_err(|x| TvdbError::CommunicationError {
-                reason: format!("Error reading response: {}", x),
-            })?;
+    /// Exchange the current JWT token for one with a later expiry time.
+    /// This is done automatically when the token is about to expire
+    /// <https://api.thetvdb.com/swagger#!/Authentication/get_refresh_token>
+    pub fn refresh_token(&self) -> TvdbResult<bool> {
+        let req = Request::new(Method::Get, Endpoint::RefreshToken.url(&self.api_base_url)?);
+        let result = self.send(self.authorized(req))?;
+        self.set_token(parse_token(&result)?);
+        Ok(true)
+    }
 
-        let deserialized: serde_json::Value = serde_json::from_str(&result)?;
-        let tok: String = deserialized["token"].as_str().unwrap().into();
-        self.set_token(tok);
+    /// Log in if there is no token yet or the token has expired (and
+    /// automatic login is enabled), or refresh it if it is about to expire
+    fn ensure_fresh_token(&self) -> TvdbResult<()> {
+        let _guard = self.lock_token();
 
-        Ok(true)
+        if self.get_token().is_none() {
+            if let Some(store) = self.token_store {
+                // An unreadable store is treated like an empty one
+                let token = store.load().unwrap_or_else(|e| {
+                    warn!("Failed to load session token: {}", e);
+                    None
+                });
+                *self.jwt_token.write().unwrap_or_else(|e| e.into_inner()) = token;
+            }
+        }
+
+        match TokenRenewal::of(self.get_token().as_ref(), self.auto_login) {
+            TokenRenewal::Keep => Ok(()),
+            TokenRenewal::Login => self.login().map(|_| ()),
+            TokenRenewal::Refresh => match self.refresh_token() {
+                Ok(_) => Ok(()),
+                Err(_) if self.auto_login => self.login().map(|_| ()),
+                // The current token is still valid for now
+                Err(e) => {
+                    warn!("Failed to refresh session token: {}", e);
+                    Ok(())
+                }
+            },
+        }
+    }
+
+    /// Credentials used to log in
+    pub fn auth(&self) -> &Auth {
+        &self.auth
+    }
+
+    /// Sets a store used to save the session token after logging in, and
+    /// to load a previously saved token instead of logging in again. Failing
+    /// to load or save the token is logged, and doesn't fail requests
+    pub fn set_token_store(&mut self, store: &'a dyn TokenStore) {
+        self.token_store = Some::<&'a dyn TokenStore>(store);
+    }
+
+    /// Whether to log in automatically, defaults to `true`. Logging in
+    /// happens before the first request, when the token has expired or
+    /// fails to refresh, and when a request is rejected with 401. When
+    /// disabled, `login` must be called explicitly, otherwise requests are
+    /// sent without a token (or with the expired one). Tokens about to
+    /// expire are still refreshed
+    pub fn set_auto_login(&mut self, enabled: bool) {
+        self.auto_login = enabled;
     }
 
     /// Sets a custom client (implementation of `RequestClient`) used to
-    /// perform HTTP requests
+    /// perform HTTP requests. This replaces the client built by
+    /// `TvdbBuilder` as a whole, including its retry policy, cache and rate
+    /// limit. To keep those, pass the client to `TvdbBuilder::http_client`
+    /// instead
     pub fn set_http_client(&mut self, client: &'a dyn RequestClient) {
-        self.http_client = Some::<&'a dyn RequestClient>(client);
+        self.http_client = Arc::new(client);
     }
 
-    /// Search for series by name or IMDB ID
-    /// <https://api.thetvdb.com/swagger#!/Search/get_search_series>
-    pub fn search(
-        &self,
-        name: Option<&str>,
-        imdb_id: Option<&str>,
-    ) -> TvdbResult<SeriesSearchResult> {
-        let dc = self.default_client.as_ref();
-        let c = self.http_client.unwrap_or(dc);
+    /// Replace the client used to perform HTTP requests, and the default
+    /// client used for downloads (unless `set_download_client` is used)
+    pub(crate) fn set_clients(
+        &mut self,
+        http_client: Arc<dyn RequestClient + 'a>,
+        default_http_client: Arc<DefaultHttpClient>,
+    ) {
+        self.http_client = http_client;
+        self.default_http_client = default_http_client;
+    }
 
-        let mut params: HashMap<&str, &str> = HashMap::new();
-        if let Some(n) = name {
-            params.insert("name", n);
+    /// Sets the language code (e.g `de`) sent with requests, defaults to `en`.
+    /// Episode lookups by `EpisodeId` use the language of the ID instead
+    pub fn set_language<S>(&mut self, language: S) -> TvdbResult<()>
+    where
+        S: Into<String>,
+    {
+        let language = language.into();
+        Language::validate_code(&language)?;
+        self.language = language;
+        Ok(())
+    }
+
+    /// Language code sent with requests
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Sets languages to fall back to, in order, when the episode name,
+    /// series name or overview is missing in the requested language. E.g
+    /// with the language set to `de` and a fallback of `["en"]`, episodes
+    /// without a German overview have the English one filled in. If a
+    /// request in a fallback language fails, that language is skipped.
+    ///
+    /// With a fallback set, the `language` field of `Episode`,
+    /// `BasicEpisode` and `Series` records which language each field came
+    /// from
+    pub fn set_language_fallback<I, S>(&mut self, languages: I) -> TvdbResult<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.language_fallback = language_codes(languages)?;
+        Ok(())
+    }
+
+    /// Sets a custom client (implementation of `DownloadClient`) used to
+    /// download artwork
+    pub fn set_download_client(&mut self, client: &'a dyn DownloadClient) {
+        self.download_client = Some::<&'a dyn DownloadClient>(client);
+    }
+
+    /// Client used to download artwork
+    pub(crate) fn download_client(&self) -> &dyn DownloadClient {
+        self.download_client
+            .unwrap_or_else(|| self.default_http_client.as_ref())
+    }
+
+    /// Host used to resolve artwork paths into URLs
+    pub fn artwork_host(&self) -> &ArtworkHost {
+        &self.artwork_host
+    }
+
+    /// Sets the base URL artwork is downloaded from, e.g a mirror. Defaults
+    /// to `https://artworks.thetvdb.com/banners/`
+    pub fn set_artwork_base_url<S>(&mut self, base_url: S)
+    where
+        S: Into<String>,
+    {
+        self.artwork_host = ArtworkHost::new(base_url);
+    }
+
+    /// Sets the base URL of the API, e.g a caching proxy or a local mock
+    /// server. Defaults to `https://api.thetvdb.com`. Fails unless given an
+    /// absolute `http` or `https` URL
+    pub fn set_api_base_url<S>(&mut self, base_url: S) -> TvdbResult<()>
+    where
+        S: Into<String>,
+    {
+        self.api_base_url = parse_base_url(&base_url.into())?;
+        Ok(())
+    }
+
+    /// Base URL of the API
+    pub fn api_base_url(&self) -> &str {
+        &self.api_base_url
+    }
+
+    /// Perform request with the appropriate client, returning the body of
+    /// successful responses
+    fn send(&self, request: Request) -> TvdbResult<String> {
+        let resp = self.client().request(&request)?;
+        check_response(&request, resp)
+    }
+
+    /// Perform request authenticated with the JWT token, refreshing it
+    /// first if needed. If the token is rejected, logs in again and retries
+    /// once (unless automatic login is disabled)
+    fn send_authorized(&self, request: Request) -> TvdbResult<String> {
+        let resp = self.request_authorized(&request)?;
+        check_response(&request, resp)
+    }
+
+    /// Like `send_authorized`, returning the response whatever its status
+    fn request_authorized(&self, request: &Request) -> TvdbResult<Response> {
+        self.ensure_fresh_token()?;
+
+        let token = self.get_token();
+        let resp = self
+            .client()
+            .request(&with_token(request.clone(), token.as_ref()))?;
+        match (resp.status, token) {
+            (401, Some(rejected)) if self.auto_login => {
+                let token = self.replace_rejected_token(&rejected)?;
+                self.client()
+                    .request(&with_token(request.clone(), token.as_ref()))
+            }
+            _ => Ok(resp),
         }
-        if let Some(i) = imdb_id {
-            params.insert("imdbId", i);
+    }
+
+    /// Log in again after the server rejected the given token, unless
+    /// another thread already replaced it meanwhile. Returns the new token
+    fn replace_rejected_token(&self, rejected: &Token) -> TvdbResult<Option<Token>> {
+        let _guard = self.lock_token();
+        if self.get_token().as_ref() == Some(rejected) {
+            self.login()?;
         }
+        Ok(self.get_token())
+    }
 
-        let search_url = "https://api.thetvdb.com/search/series";
-        let url: String = url::Url::parse_with_params(search_url, params)
-            .unwrap()
-            .as_str()
-            .into();
-        // Query URL
-        let data = c.get_url(&url, self.get_token())?;
+    /// Add the JWT token, if any, to the request
+    fn authorized(&self, request: Request) -> Request {
+        with_token(request, self.get_token().as_ref())
+    }
 
+    /// Fetch the endpoint, authenticated with the JWT token and sending the
+    /// given language, and parse the JSON response
+    fn get<T>(&self, endpoint: Endpoint<'_>, language: &str) -> TvdbResult<T>
+    where
+        T: DeserializeOwned,
+    {
+        let data = self.send_authorized(endpoint.get(&self.api_base_url, language)?)?;
         // Parse result
-        let result: SeriesSearchResult = serde_json::from_str(&data)?;
+        Ok(serde_json::from_str(&data)?)
+    }
 
-        Ok(result)
+    /// Like `get`, filling translatable fields missing in `language` from
+    /// the fallback languages. This is best effort: languages which fail to
+    /// fetch are skipped
+    fn get_translated<T>(&self, endpoint: Endpoint<'_>, language: &str) -> TvdbResult<T>
+    where
+        T: DeserializeOwned + TranslatableResult,
+    {
+        let result = self.get(endpoint, language)?;
+        Ok(self.fill_fallback(endpoint, language, result))
     }
 
-    fn episode_inner(&self, id: EpisodeId) -> TvdbResult<EpisodeRecordResult> {
-        let dc = self.default_client.as_ref();
-        let c = self.http_client.unwrap_or(dc);
+    /// Like `get_translated`, but `None` if the server responds with 404
+    fn get_translated_if_found<T>(
+        &self,
+        endpoint: Endpoint<'_>,
+        language: &str,
+    ) -> TvdbResult<Option<T>>
+    where
+        T: DeserializeOwned + TranslatableResult,
+    {
+        let request = endpoint.get(&self.api_base_url, language)?;
+        let resp = self.request_authorized(&request)?;
+        if resp.status == 404 {
+            return Ok(None);
+        }
+        let result = serde_json::from_str(&check_response(&request, resp)?)?;
+        Ok(Some(self.fill_fallback(endpoint, language, result)))
+    }
 
-        // TODO Use `id.language`
+    /// Fill fields of `result` missing in `language` from the fallback
+    /// languages, skipping those which fail to fetch
+    fn fill_fallback<T>(&self, endpoint: Endpoint<'_>, language: &str, mut result: T) -> T
+    where
+        T: DeserializeOwned + TranslatableResult,
+    {
+        let mut fallback = Fallback::new(language, &self.language_fallback, result.items_mut());
+        while let Some(other_language) = fallback.next_language(result.items()) {
+            match self.get::<T>(endpoint, other_language) {
+                Ok(other) => fallback.fill(result.items_mut(), other.items(), other_language),
+                Err(e) => warn!(
+                    "Failed to fetch fallback language {}: {}",
+                    other_language, e
+                ),
+            }
+        }
+        result
+    }
 
-        let url = format!("https://api.thetvdb.com/episodes/{id}", id = id.seriesid);
-        let data = c.get_url(&url, self.get_token())?;
+    /// Perform request with the given method (e.g PUT), authenticated with
+    /// the JWT token, and parse the JSON response
+    fn modify<T>(&self, method: Method, endpoint: Endpoint<'_>) -> TvdbResult<T>
+    where
+        T: DeserializeOwned,
+    {
+        let req = Request::new(method, endpoint.url(&self.api_base_url)?);
+        let data = self.send_authorized(req)?;
         // Parse result
-        let result: EpisodeRecordResult = serde_json::from_str(&data)?;
-        return Ok(result);
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Client used to perform HTTP requests
+    fn client(&self) -> &dyn RequestClient {
+        self.http_client.as_ref()
+    }
+
+    /// Search for series by name or IMDB ID
+    /// <https://api.thetvdb.com/swagger#!/Search/get_search_series>
+    pub fn search(
+        &self,
+        name: Option<&str>,
+        imdb_id: Option<&str>,
+    ) -> TvdbResult<SeriesSearchResult> {
+        self.get(Endpoint::Search { name, imdb_id }, &self.language)
     }
 
     /// Full information about given episode
@@ -184,24 +1072,26 @@ impl<'a> Tvdb<'a> {
     where
         E: Into<EpisodeId>,
     {
-        self.episode_inner(id.into())
+        let id = id.into();
+        self.get_translated(Endpoint::Episode(id.seriesid), &id.language)
     }
 
-    fn series_episodes_inner(&self, id: SeriesId, page: u32) -> TvdbResult<SeriesEpisodesResult> {
-        let dc = self.default_client.as_ref();
-        let c = self.http_client.unwrap_or(dc);
-
-        // TODO Use `id.language`
+    /// Full information about given series
+    /// <https://api.thetvdb.com/swagger#!/Series/get_series_id>
+    pub fn series<S>(&self, id: S) -> TvdbResult<SeriesRecordResult>
+    where
+        S: Into<SeriesId>,
+    {
+        self.get_translated(Endpoint::Series(id.into().seriesid), &self.language)
+    }
 
-        let url = format!(
-            "https://api.thetvdb.com/series/{id}/episodes?page={page}",
-            id = id.seriesid,
-            page = page
-        );
-        let data = c.get_url(&url, self.get_token())?;
-        // Parse result
-        let result: Result<SeriesEpisodesResult, serde_json::Error> = serde_json::from_str(&data);
-        return Ok(result?);
+    /// Actors for given series
+    /// <https://api.thetvdb.com/swagger#!/Series/get_series_id_actors>
+    pub fn actors<S>(&self, id: S) -> TvdbResult<SeriesActorsResult>
+    where
+        S: Into<SeriesId>,
+    {
+        self.get(Endpoint::Actors(id.into().seriesid), &self.language)
     }
 
     /// All episodes for given series
@@ -209,6 +1099,213 @@ impl<'a> Tvdb<'a> {
     where
         S: Into<SeriesId>,
     {
-        self.series_episodes_inner(id.into(), page)
+        let id = id.into().seriesid;
+        self.get_translated(Endpoint::SeriesEpisodes { id, page }, &self.language)
+    }
+
+    /// Like `series_episodes`, but `None` if TheTVDB responds with 404,
+    /// which it does for series without any episodes as well as for series
+    /// which no longer exist
+    pub(crate) fn series_episodes_if_found(
+        &self,
+        id: u32,
+        page: u32,
+    ) -> TvdbResult<Option<SeriesEpisodesResult>> {
+        self.get_translated_if_found(Endpoint::SeriesEpisodes { id, page }, &self.language)
+    }
+
+    /// Number of seasons and episodes for given series
+    /// <https://api.thetvdb.com/swagger#!/Series/get_series_id_episodes_summary>
+    pub fn series_episodes_summary<S>(&self, id: S) -> TvdbResult<EpisodesSummaryResult>
+    where
+        S: Into<SeriesId>,
+    {
+        self.get(
+            Endpoint::SeriesEpisodesSummary(id.into().seriesid),
+            &self.language,
+        )
+    }
+
+    /// Number of images available for given series, by type
+    /// <https://api.thetvdb.com/swagger#!/Series/get_series_id_images>
+    pub fn series_images<S>(&self, id: S) -> TvdbResult<SeriesImagesResult>
+    where
+        S: Into<SeriesId>,
+    {
+        self.get(Endpoint::SeriesImages(id.into().seriesid), &self.language)
+    }
+
+    /// Images for given series matching the query
+    /// <https://api.thetvdb.com/swagger#!/Series/get_series_id_images_query>
+    pub fn series_images_query<S>(
+        &self,
+        id: S,
+        query: &ImageQuery,
+    ) -> TvdbResult<SeriesImagesQueryResult>
+    where
+        S: Into<SeriesId>,
+    {
+        let id = id.into().seriesid;
+        self.get(Endpoint::SeriesImagesQuery { id, query }, &self.language)
+    }
+
+    /// Episodes for given series matching the query, e.g by aired season and
+    /// episode number
+    /// <https://api.thetvdb.com/swagger#!/Series/get_series_id_episodes_query>
+    pub fn series_episodes_query<S>(
+        &self,
+        id: S,
+        query: &EpisodeQuery,
+        page: u32,
+    ) -> TvdbResult<SeriesEpisodesResult>
+    where
+        S: Into<SeriesId>,
+    {
+        let id = id.into().seriesid;
+        self.get_translated(
+            Endpoint::SeriesEpisodesQuery { id, query, page },
+            &self.language,
+        )
+    }
+
+    /// All languages supported by TheTVDB
+    /// <https://api.thetvdb.com/swagger#!/Languages/get_languages>
+    pub fn languages(&self) -> TvdbResult<LanguagesResult> {
+        self.get(Endpoint::Languages, &self.language)
+    }
+
+    /// Information about the language with given ID (as found in e.g
+    /// `Image::language_id`)
+    /// <https://api.thetvdb.com/swagger#!/Languages/get_languages_id>
+    pub fn language_by_id(&self, id: u32) -> TvdbResult<LanguageResult> {
+        self.get(Endpoint::Language(id), &self.language)
+    }
+
+    /// Series changed between the `from` and `to` times (in seconds since
+    /// the Unix epoch), with `to` defaulting to now. Ranges longer than
+    /// TheTVDB's one week limit are split into multiple requests. Each
+    /// series appears once with its latest update time, ordered by update
+    /// time
+    /// <https://api.thetvdb.com/swagger#!/Updates/get_updated_query>
+    pub fn updated_since(&self, from: u32, to: Option<u32>) -> TvdbResult<Vec<UpdatedSeries>> {
+        let mut found = vec![];
+        for (from, to) in updated_windows(from, to)? {
+            let result: UpdatedResult = self.get(Endpoint::Updated { from, to }, &self.language)?;
+            found.extend(result.data.unwrap_or_default());
+        }
+        Ok(merge_updated(found))
+    }
+
+    /// Information about the logged in user, requires logging in with user
+    /// credentials
+    /// <https://api.thetvdb.com/swagger#!/Users/get_user>
+    pub fn user(&self) -> TvdbResult<UserResult> {
+        self.get(Endpoint::User, &self.language)
+    }
+
+    /// IDs of the user's favorite series
+    /// <https://api.thetvdb.com/swagger#!/Users/get_user_favorites>
+    pub fn favorites(&self) -> TvdbResult<UserFavoritesResult> {
+        self.get(Endpoint::Favorites, &self.language)
+    }
+
+    /// Add series to the user's favorites, returning the updated favorites
+    /// <https://api.thetvdb.com/swagger#!/Users/put_user_favorites_id>
+    pub fn add_favorite<S>(&self, id: S) -> TvdbResult<UserFavoritesResult>
+    where
+        S: Into<SeriesId>,
+    {
+        self.modify(Method::Put, Endpoint::Favorite(id.into().seriesid))
+    }
+
+    /// Remove series from the user's favorites, returning the updated
+    /// favorites
+    /// <https://api.thetvdb.com/swagger#!/Users/delete_user_favorites_id>
+    pub fn remove_favorite<S>(&self, id: S) -> TvdbResult<UserFavoritesResult>
+    where
+        S: Into<SeriesId>,
+    {
+        self.modify(Method::Delete, Endpoint::Favorite(id.into().seriesid))
+    }
+
+    /// All of the user's ratings
+    /// <https://api.thetvdb.com/swagger#!/Users/get_user_ratings>
+    pub fn ratings(&self) -> TvdbResult<UserRatingsResult> {
+        self.get(Endpoint::Ratings, &self.language)
+    }
+
+    /// The user's ratings of the given type of item
+    /// <https://api.thetvdb.com/swagger#!/Users/get_user_ratings_query>
+    pub fn ratings_query(&self, item_type: RatingType) -> TvdbResult<UserRatingsResult> {
+        self.get(Endpoint::RatingsQuery(item_type), &self.language)
+    }
+
+    /// Rate the given item, replacing any existing rating
+    /// <https://api.thetvdb.com/swagger#!/Users/put_user_ratings_itemType_itemId_itemRating>
+    pub fn add_rating(
+        &self,
+        item_type: RatingType,
+        item_id: u32,
+        rating: u32,
+    ) -> TvdbResult<UserRatingsResult> {
+        let endpoint = Endpoint::SetRating {
+            item_type,
+            item_id,
+            rating,
+        };
+        self.modify(Method::Put, endpoint)
+    }
+
+    /// Remove the user's rating of the given item
+    /// <https://api.thetvdb.com/swagger#!/Users/delete_user_ratings_itemType_itemId>
+    pub fn delete_rating(
+        &self,
+        item_type: RatingType,
+        item_id: u32,
+    ) -> TvdbResult<UserRatingsResult> {
+        self.modify(Method::Delete, Endpoint::Rating { item_type, item_id })
+    }
+
+    fn season_episode_inner(&self, id: SeriesId, season: u32, episode: u32) -> TvdbResult<Episode> {
+        let query = season_episode_query(season, episode);
+        let endpoint = Endpoint::SeriesEpisodesQuery {
+            id: id.seriesid,
+            query: &query,
+            page: 1,
+        };
+        let epid = first_episode_id(self.get(endpoint, &self.language)?)?;
+
+        self.episode(EpisodeId::new(epid, &self.language))?
+            .data
+            .ok_or(TvdbError::EpisodeNotFound)
+    }
+
+    /// Full information about the episode with the given aired season and
+    /// episode number. The series can also be given as a search result,
+    /// failing with `SeriesNotFound` if it has no ID
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # let MY_API_KEY = "0629B785CE550C8D";
+    /// let api = tvdb::Tvdb::new(MY_API_KEY);
+    ///
+    /// // Lookup the 23rd episode of season 1 for the given series ID:
+    /// let ep_by_id = api.season_episode(76156, 1, 23).unwrap();
+    /// println!("{}", ep_by_id.episode_name);
+    ///
+    /// // More commonly, perform search for series
+    /// let sr = api.search(Some("scrubs"), None).unwrap();
+    /// let first_result = &sr.data.unwrap()[0];
+    ///
+    /// // ..then lookup the 23rd episode of the first result:
+    /// let ep = api.season_episode(first_result, 1, 23).unwrap();
+    /// println!("{}", ep.episode_name);
+    /// ```
+    pub fn season_episode<S>(&self, id: S, season: u32, episode: u32) -> TvdbResult<Episode>
+    where
+        S: TryInto<SeriesId>,
+        TvdbError: From<S::Error>,
+    {
+        self.season_episode_inner(id.try_into()?, season, episode)
     }
 }
@@ -0,0 +1,227 @@
+//! Retrying requests which failed due to transient errors
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::error::{TvdbError, TvdbResult};
+use crate::raw::{Request, RequestClient, Response};
+
+/// Status codes retried by default: rate limiting, and server errors which
+/// are usually temporary
+pub const DEFAULT_RETRY_STATUSES: [u16; 5] = [429, 500, 502, 503, 504];
+
+/// How often, and how long apart, to attempt a request.
+///
+/// The delay before each retry grows exponentially, starting at `delay`
+/// and multiplied by `backoff_factor` for each further retry, up to
+/// `max_delay`. With jitter enabled, a random delay between half and all of
+/// that is used instead, so many clients failing at the same time don't
+/// retry in lockstep. When the response has a `Retry-After` header, that
+/// delay is used instead, unless it exceeds `max_delay` in which case the
+/// response is returned without retrying.
+///
+/// Failures to communicate with the server (e.g connection resets and
+/// timeouts) and responses with one of the retry statuses are retried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    delay: Duration,
+    max_delay: Duration,
+    backoff_factor: u32,
+    jitter: bool,
+    retry_statuses: Vec<u16>,
+    retry_errors: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy::none()
+    }
+}
+
+impl RetryPolicy {
+    /// Make each request only once
+    pub fn none() -> RetryPolicy {
+        RetryPolicy::new(1)
+    }
+
+    /// Make each request up to `max_attempts` times (including the first
+    /// attempt), starting with a one second delay which doubles with each
+    /// retry, up to 30 seconds
+    pub fn new(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            backoff_factor: 2,
+            jitter: true,
+            retry_statuses: DEFAULT_RETRY_STATUSES.to_vec(),
+            retry_errors: true,
+        }
+    }
+
+    /// Sets the time to wait before the first retry
+    pub fn delay(mut self, delay: Duration) -> RetryPolicy {
+        self.delay = delay;
+        self
+    }
+
+    /// Sets the longest time to wait before a retry, including delays
+    /// requested with `Retry-After`
+    pub fn max_delay(mut self, max_delay: Duration) -> RetryPolicy {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Sets the factor the delay is multiplied by for each further retry,
+    /// e.g 1 for a constant delay
+    pub fn backoff_factor(mut self, factor: u32) -> RetryPolicy {
+        self.backoff_factor = factor.max(1);
+        self
+    }
+
+    /// Whether to randomise delays
+    pub fn jitter(mut self, jitter: bool) -> RetryPolicy {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the response status codes which are retried, replacing
+    /// `DEFAULT_RETRY_STATUSES`
+    pub fn retry_statuses<I>(mut self, statuses: I) -> RetryPolicy
+    where
+        I: IntoIterator<Item = u16>,
+    {
+        self.retry_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Whether to retry failures to communicate with the server
+    pub fn retry_errors(mut self, retry: bool) -> RetryPolicy {
+        self.retry_errors = retry;
+        self
+    }
+
+    /// Maximum number of attempts per request
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Whether a request with the given result is worth attempting again
+    pub fn is_retryable(&self, result: &TvdbResult<Response>) -> bool {
+        match *result {
+            Ok(ref resp) => self.retry_statuses.contains(&resp.status),
+            Err(TvdbError::CommunicationError { .. }) | Err(TvdbError::IoError { .. }) => {
+                self.retry_errors
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Time to wait before making the given retry (1 for the first retry)
+    /// after a request with the given result, or `None` if the request
+    /// should not be retried
+    pub fn retry_delay(&self, retry: u32, result: &TvdbResult<Response>) -> Option<Duration> {
+        if retry >= self.max_attempts || !self.is_retryable(result) {
+            return None;
+        }
+
+        if let Ok(ref resp) = *result {
+            if let Some(wait) = retry_after(resp) {
+                return if wait <= self.max_delay {
+                    Some(wait)
+                } else {
+                    None
+                };
+            }
+        }
+
+        let factor = self.backoff_factor.saturating_pow(retry - 1);
+        let delay = self
+            .delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |d| d.min(self.max_delay));
+        if self.jitter {
+            let half = delay / 2;
+            let nanos = half.as_nanos() as u64;
+            let extra = if nanos == 0 {
+                0
+            } else {
+                random() % (nanos + 1)
+            };
+            Some(half + Duration::from_nanos(extra))
+        } else {
+            Some(delay)
+        }
+    }
+}
+
+/// Delay requested with the `Retry-After` header, either in seconds or as
+/// an HTTP date
+fn retry_after(resp: &Response) -> Option<Duration> {
+    let value = resp.header_value("Retry-After")?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = httpdate::parse_http_date(value).ok()?;
+    Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+/// Random number for jitter, not suitable for anything needing actual
+/// randomness
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// Wraps a `RequestClient`, retrying failed requests according to a
+/// `RetryPolicy`
+#[derive(Debug)]
+pub struct RetryClient<C> {
+    inner: C,
+    policy: RetryPolicy,
+}
+
+impl<C> RetryClient<C>
+where
+    C: RequestClient,
+{
+    pub fn new(inner: C, policy: RetryPolicy) -> RetryClient<C> {
+        RetryClient { inner, policy }
+    }
+
+    /// Policy used to retry requests
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+}
+
+impl<C> RequestClient for RetryClient<C>
+where
+    C: RequestClient,
+{
+    fn request(&self, request: &Request) -> TvdbResult<Response> {
+        let mut retry = 1;
+        loop {
+            let result = self.inner.request(request);
+            let delay = match self.policy.retry_delay(retry, &result) {
+                Some(d) => d,
+                None => return result,
+            };
+
+            match result {
+                Ok(ref resp) => debug!(
+                    "Retrying {} after HTTP status {} in {:?}",
+                    request.url, resp.status, delay
+                ),
+                Err(ref e) => debug!(
+                    "Retrying {} after error ({}) in {:?}",
+                    request.url, e, delay
+                ),
+            }
+            thread::sleep(delay);
+            retry += 1;
+        }
+    }
+}
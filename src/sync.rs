@@ -0,0 +1,214 @@
+//! Incrementally keeping a local copy of series' episodes up to date, using
+//! the updated series feed
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::data::{BasicEpisode, SeriesId};
+use crate::error::{TvdbError, TvdbResult};
+use crate::raw::Tvdb;
+
+/// Changes to the episodes of a single series
+#[derive(Debug, Clone)]
+pub struct SeriesChanges {
+    pub series_id: u32,
+    /// Episodes not seen before
+    pub added: Vec<BasicEpisode>,
+    /// Episodes whose `last_updated` time changed
+    pub modified: Vec<BasicEpisode>,
+    /// IDs of episodes which no longer exist
+    pub removed: Vec<u32>,
+}
+
+impl SeriesChanges {
+    /// Whether no episodes changed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Result of a sync, containing only series with changed episodes
+#[derive(Debug, Clone, Default)]
+pub struct ChangeSet {
+    pub series: Vec<SeriesChanges>,
+}
+
+impl ChangeSet {
+    /// Whether no episodes changed
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+}
+
+/// Tracks a set of series, and on each `sync` refetches the episodes of
+/// those which the updated feed reports as changed since the last sync.
+///
+/// Series are identified by their ID, and episodes by ID along with their
+/// `last_updated` time. Tracked series with no known episodes are always
+/// fetched, so the first sync reports every episode as added. A series
+/// which TheTVDB reports as not found is treated as having no episodes, so
+/// its known episodes are reported as removed
+#[derive(Debug, Clone)]
+pub struct SeriesSync {
+    tracked: BTreeSet<u32>,
+    last_sync: u32,
+    known: HashMap<u32, HashMap<u32, Option<u32>>>,
+}
+
+impl SeriesSync {
+    /// Sync the given series, with changes since `last_sync` (in seconds
+    /// since the Unix epoch)
+    pub fn new<I, S>(tracked: I, last_sync: u32) -> SeriesSync
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<SeriesId>,
+    {
+        SeriesSync {
+            tracked: tracked.into_iter().map(|s| s.into().seriesid).collect(),
+            last_sync,
+            known: HashMap::new(),
+        }
+    }
+
+    /// Start tracking a series
+    pub fn track<S>(&mut self, series: S)
+    where
+        S: Into<SeriesId>,
+    {
+        self.tracked.insert(series.into().seriesid);
+    }
+
+    /// Stop tracking a series, forgetting its known episodes
+    pub fn untrack<S>(&mut self, series: S)
+    where
+        S: Into<SeriesId>,
+    {
+        let id = series.into().seriesid;
+        self.tracked.remove(&id);
+        self.known.remove(&id);
+    }
+
+    /// IDs of the tracked series
+    pub fn tracked(&self) -> &BTreeSet<u32> {
+        &self.tracked
+    }
+
+    /// Time of the last sync, in seconds since the Unix epoch
+    pub fn last_sync(&self) -> u32 {
+        self.last_sync
+    }
+
+    /// Known episode IDs of a series, with their `last_updated` time
+    pub fn known_episodes<S>(&self, series: S) -> Option<&HashMap<u32, Option<u32>>>
+    where
+        S: Into<SeriesId>,
+    {
+        self.known.get(&series.into().seriesid)
+    }
+
+    /// Set the known episodes (ID and `last_updated` time) of a series, e.g
+    /// to restore the state of a previous run
+    pub fn set_known_episodes<S, I>(&mut self, series: S, episodes: I)
+    where
+        S: Into<SeriesId>,
+        I: IntoIterator<Item = (u32, Option<u32>)>,
+    {
+        self.known
+            .insert(series.into().seriesid, episodes.into_iter().collect());
+    }
+
+    /// Sync up to the current time
+    pub fn sync(&mut self, tvdb: &Tvdb) -> TvdbResult<ChangeSet> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as u32)
+            .map_err(|e| TvdbError::InternalError {
+                reason: format!("System time before Unix epoch: {}", e),
+            })?;
+        self.sync_until(tvdb, now)
+    }
+
+    /// Sync changes up to the given time. State is only updated once all
+    /// series were fetched successfully, so a failed sync can be retried
+    pub fn sync_until(&mut self, tvdb: &Tvdb, now: u32) -> TvdbResult<ChangeSet> {
+        // Series which were never fetched, and those which changed
+        let mut to_fetch: BTreeSet<u32> = self
+            .tracked
+            .iter()
+            .filter(|id| !self.known.contains_key(id))
+            .cloned()
+            .collect();
+        if self.tracked.iter().any(|id| self.known.contains_key(id)) {
+            for updated in tvdb.updated_since(self.last_sync, Some(now))? {
+                if self.tracked.contains(&updated.id) {
+                    to_fetch.insert(updated.id);
+                }
+            }
+        }
+
+        let mut fetched = BTreeMap::new();
+        for id in to_fetch {
+            fetched.insert(id, all_episodes(tvdb, id)?);
+        }
+
+        let mut changes = ChangeSet::default();
+        for (id, episodes) in fetched {
+            let previous = self.known.remove(&id).unwrap_or_default();
+            let mut current = HashMap::new();
+            let mut series = SeriesChanges {
+                series_id: id,
+                added: vec![],
+                modified: vec![],
+                removed: vec![],
+            };
+
+            for ep in episodes {
+                let epid = match ep.id {
+                    Some(epid) => epid,
+                    None => continue,
+                };
+                current.insert(epid, ep.last_updated);
+                match previous.get(&epid) {
+                    None => series.added.push(ep),
+                    Some(last_updated) if *last_updated != ep.last_updated => {
+                        series.modified.push(ep)
+                    }
+                    Some(_) => (),
+                }
+            }
+            series.removed = previous
+                .keys()
+                .filter(|epid| !current.contains_key(epid))
+                .cloned()
+                .collect();
+            series.removed.sort();
+
+            self.known.insert(id, current);
+            if !series.is_empty() {
+                changes.series.push(series);
+            }
+        }
+
+        self.last_sync = now;
+        Ok(changes)
+    }
+}
+
+/// Every page of episodes for the series. TheTVDB responds with 404 for
+/// series without episodes (or which were deleted), so that is treated as
+/// having no episodes rather than failing the whole sync
+fn all_episodes(tvdb: &Tvdb, id: u32) -> TvdbResult<Vec<BasicEpisode>> {
+    let mut episodes = vec![];
+    let mut page = 1;
+    loop {
+        let result = match tvdb.series_episodes_if_found(id, page)? {
+            Some(result) => result,
+            None => return Ok(episodes),
+        };
+        episodes.extend(result.data.unwrap_or_default());
+        match result.links.and_then(|l| l.next) {
+            Some(next) if next > page => page = next,
+            _ => return Ok(episodes),
+        }
+    }
+}
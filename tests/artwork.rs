@@ -0,0 +1,214 @@
+extern crate serde_json;
+extern crate tvdb;
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use tvdb::artwork::{DownloadClient, DownloadOutcome};
+use tvdb::data::{Image, SeriesSearchData};
+use tvdb::{ArtworkHost, ArtworkSize, Tvdb, TvdbError, TvdbResult};
+
+/// Writes the requested URL as the downloaded content, remembering each request
+#[derive(Debug)]
+struct EchoDownloadClient {
+    requested: Mutex<Vec<String>>,
+}
+
+impl EchoDownloadClient {
+    pub fn new() -> EchoDownloadClient {
+        EchoDownloadClient {
+            requested: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl DownloadClient for EchoDownloadClient {
+    fn download(&self, url: &str, dest: &mut dyn Write) -> TvdbResult<u64> {
+        self.requested.lock().unwrap().push(url.into());
+        if url.contains("missing") {
+            return Err(TvdbError::CommunicationError {
+                reason: format!("Not found: {}", url),
+            });
+        }
+        dest.write_all(url.as_bytes())?;
+        Ok(url.len() as u64)
+    }
+}
+
+/// Empty directory unique to the given test
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("tvdb-rs-test-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn poster() -> Image {
+    serde_json::from_str(
+        r#"{"id": 1, "keyType": "poster", "fileName": "posters/76156-1.jpg",
+            "thumbnail": "_cache/posters/76156-1.jpg", "resolution": "680x1000"}"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn resolve_paths() {
+    let host = ArtworkHost::default();
+    assert_eq!(
+        host.url("graphical/76156-g.jpg", ArtworkSize::Original),
+        "https://artworks.thetvdb.com/banners/graphical/76156-g.jpg"
+    );
+    assert_eq!(
+        host.url("/graphical/76156-g.jpg", ArtworkSize::Thumbnail),
+        "https://artworks.thetvdb.com/banners/_cache/graphical/76156-g.jpg"
+    );
+    assert_eq!(
+        host.url("_cache/graphical/76156-g.jpg", ArtworkSize::Thumbnail),
+        "https://artworks.thetvdb.com/banners/_cache/graphical/76156-g.jpg"
+    );
+    assert_eq!(
+        host.url("http://example.com/x.jpg", ArtworkSize::Thumbnail),
+        "http://example.com/x.jpg"
+    );
+}
+
+#[test]
+fn custom_host() {
+    let host = ArtworkHost::new("http://localhost:8080/art");
+    assert_eq!(host.base_url(), "http://localhost:8080/art/");
+
+    let sr: SeriesSearchData = serde_json::from_str(
+        r#"{"seriesName": "Scrubs", "id": 76156, "banner": "graphical/76156-g.jpg"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        sr.banner_url(&host, ArtworkSize::Original).unwrap(),
+        "http://localhost:8080/art/graphical/76156-g.jpg"
+    );
+
+    let empty: SeriesSearchData =
+        serde_json::from_str(r#"{"seriesName": "Scrubs", "id": 76156, "banner": ""}"#).unwrap();
+    assert_eq!(empty.banner_url(&host, ArtworkSize::Original), None);
+}
+
+#[test]
+fn image_urls() {
+    let host = ArtworkHost::default();
+    let img: Image = serde_json::from_str(
+        r#"{"id": 1, "keyType": "poster", "fileName": "posters/76156-1.jpg",
+            "thumbnail": "_cache/posters/76156-1.jpg", "resolution": "680x1000"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        img.url(&host, ArtworkSize::Original).unwrap(),
+        "https://artworks.thetvdb.com/banners/posters/76156-1.jpg"
+    );
+    assert_eq!(
+        img.url(&host, ArtworkSize::Thumbnail).unwrap(),
+        "https://artworks.thetvdb.com/banners/_cache/posters/76156-1.jpg"
+    );
+}
+
+#[test]
+fn image_local_path() {
+    let img = poster();
+    assert_eq!(
+        img.local_path(76156, ArtworkSize::Original).unwrap(),
+        PathBuf::from("76156/poster/680x1000/76156-1.jpg")
+    );
+    assert_eq!(
+        img.local_path(76156, ArtworkSize::Thumbnail).unwrap(),
+        PathBuf::from("76156/poster/thumbnail/76156-1.jpg")
+    );
+}
+
+#[test]
+fn image_local_path_traversal() {
+    let img: Image = serde_json::from_str(
+        r#"{"id": 1, "keyType": "../../../tmp/evil", "fileName": "posters/76156-1.jpg",
+            "resolution": ".."}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        img.local_path(76156, ArtworkSize::Original).unwrap(),
+        PathBuf::from("76156/unknown/original/76156-1.jpg")
+    );
+
+    let img: Image = serde_json::from_str(
+        r#"{"id": 1, "keyType": "poster\\..", "fileName": "x/..", "resolution": "a"}"#,
+    )
+    .unwrap();
+    assert_eq!(img.local_path(76156, ArtworkSize::Original), None);
+}
+
+#[test]
+fn download_to_writer() {
+    let c = EchoDownloadClient::new();
+    let mut api = Tvdb::new("");
+    api.set_download_client(&c);
+
+    let mut buf = vec![];
+    let bytes = api
+        .download_artwork("graphical/76156-g.jpg", ArtworkSize::Original, &mut buf)
+        .unwrap();
+    let expected = "https://artworks.thetvdb.com/banners/graphical/76156-g.jpg";
+    assert_eq!(bytes, expected.len() as u64);
+    assert_eq!(String::from_utf8(buf).unwrap(), expected);
+}
+
+#[test]
+fn download_image_to_dir() {
+    let dir = scratch_dir("download_image_to_dir");
+    let c = EchoDownloadClient::new();
+    let mut api = Tvdb::new("");
+    api.set_download_client(&c);
+
+    let img = poster();
+    let first = api
+        .download_image(76156, &img, ArtworkSize::Original, &dir)
+        .unwrap();
+    let path = dir.join("76156/poster/680x1000/76156-1.jpg");
+    match first {
+        DownloadOutcome::Downloaded { path: ref p, .. } => assert_eq!(p, &path),
+        ref other => panic!("Unexpected outcome {:?}", other),
+    }
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "https://artworks.thetvdb.com/banners/posters/76156-1.jpg"
+    );
+
+    // Second download is skipped
+    let second = api
+        .download_image(76156, &img, ArtworkSize::Original, &dir)
+        .unwrap();
+    assert_eq!(second, DownloadOutcome::Skipped { path: path.clone() });
+    assert_eq!(c.requested.lock().unwrap().len(), 1);
+
+    // Thumbnail uses the provided thumbnail path
+    api.download_image(76156, &img, ArtworkSize::Thumbnail, &dir)
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.join("76156/poster/thumbnail/76156-1.jpg")).unwrap(),
+        "https://artworks.thetvdb.com/banners/_cache/posters/76156-1.jpg"
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn failed_download_leaves_no_file() {
+    let dir = scratch_dir("failed_download_leaves_no_file");
+    let c = EchoDownloadClient::new();
+    let mut api = Tvdb::new("");
+    api.set_download_client(&c);
+
+    let target = dir.join("missing.jpg");
+    let result = api.download_artwork_to_file("missing.jpg", ArtworkSize::Original, &target);
+    assert!(result.is_err());
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
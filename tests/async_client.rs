@@ -0,0 +1,160 @@
+#![cfg(feature = "async")]
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll, Waker};
+
+use tvdb::async_client::{AsyncRequestClient, AsyncTvdb, BoxFuture};
+use tvdb::{Method, Request, Response, TvdbResult};
+
+/// Run a future which never waits on I/O, as is the case with
+/// `CannedAsyncClient`
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(out) = future.as_mut().poll(&mut cx) {
+            return out;
+        }
+    }
+}
+
+/// Returns canned responses for known requests, keyed by method and URL,
+/// and a 404 response for anything else. Requests are recorded
+#[derive(Debug)]
+struct CannedAsyncClient {
+    responses: HashMap<String, String>,
+    sent: Mutex<Vec<Request>>,
+}
+
+impl CannedAsyncClient {
+    pub fn new() -> CannedAsyncClient {
+        let mut c = CannedAsyncClient {
+            responses: HashMap::new(),
+            sent: Mutex::new(vec![]),
+        };
+        c.respond(
+            "POST",
+            "https://api.thetvdb.com/login",
+            r#"{"token": "abc.def.ghi"}"#,
+        );
+        c
+    }
+
+    pub fn respond(&mut self, method: &str, url: &str, body: &str) {
+        self.responses
+            .insert(format!("{} {}", method, url), body.into());
+    }
+
+    /// Respond only to GET requests in the given language
+    pub fn respond_lang(&mut self, url: &str, language: &str, body: &str) {
+        self.responses
+            .insert(format!("GET {} [{}]", url, language), body.into());
+    }
+
+    pub fn sent(&self) -> Vec<Request> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+impl AsyncRequestClient for CannedAsyncClient {
+    fn request<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, TvdbResult<Response>> {
+        Box::pin(async move {
+            self.sent.lock().unwrap().push(request.clone());
+            let key = format!("{} {}", request.method.as_str(), request.url);
+            let lang_key = format!(
+                "{} [{}]",
+                key,
+                request.header_value("Accept-Language").unwrap_or("")
+            );
+            match self
+                .responses
+                .get(&lang_key)
+                .or_else(|| self.responses.get(&key))
+            {
+                Some(body) => Ok(Response::new(200, body.as_str())),
+                None => Ok(Response::new(404, r#"{"Error": "Not found"}"#)),
+            }
+        })
+    }
+}
+
+#[test]
+fn series_async() {
+    let mut c = CannedAsyncClient::new();
+    c.respond(
+        "GET",
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156, "seriesName": "Scrubs"}}"#,
+    );
+
+    let mut api = AsyncTvdb::new("0629B785CE550C8D");
+    api.set_http_client(&c);
+    let series = block_on(api.series(76156)).unwrap().data.unwrap();
+    assert_eq!(series.series_name.unwrap(), "Scrubs");
+
+    let sent = c.sent();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[0].method, Method::Post);
+    assert_eq!(
+        sent[1].header_value("Authorization"),
+        Some("Bearer abc.def.ghi")
+    );
+    assert_eq!(sent[1].header_value("Accept-Language"), Some("en"));
+}
+
+#[test]
+fn season_episode_async() {
+    let mut c = CannedAsyncClient::new();
+    c.respond(
+        "GET",
+        "https://api.thetvdb.com/series/76156/episodes/query?airedSeason=1&airedEpisode=23&page=1",
+        r#"{"data": [{"id": 184603, "airedSeason": 1, "airedEpisodeNumber": 23}]}"#,
+    );
+    c.respond(
+        "GET",
+        "https://api.thetvdb.com/episodes/184603",
+        r#"{"data": {"id": 184603, "episodeName": "My Mentor"}}"#,
+    );
+
+    let mut api = AsyncTvdb::new("0629B785CE550C8D");
+    api.set_http_client(&c);
+    let ep = block_on(api.season_episode(76156, 1, 23)).unwrap();
+    assert_eq!(ep.episode_name, "My Mentor");
+}
+
+#[test]
+fn unsuccessful_response_async() {
+    let c = CannedAsyncClient::new();
+    let mut api = AsyncTvdb::new("0629B785CE550C8D");
+    api.set_http_client(&c);
+    assert!(block_on(api.series(1)).is_err());
+}
+
+#[test]
+fn language_fallback_async() {
+    let mut c = CannedAsyncClient::new();
+    c.respond_lang(
+        "https://api.thetvdb.com/series/76156",
+        "de",
+        r#"{"data": {"id": 76156, "seriesName": "Scrubs – Die Anfänger", "overview": null}}"#,
+    );
+    c.respond_lang(
+        "https://api.thetvdb.com/series/76156",
+        "en",
+        r#"{"data": {"id": 76156, "seriesName": "Scrubs", "overview": "English"}}"#,
+    );
+
+    let mut api = AsyncTvdb::new("0629B785CE550C8D");
+    api.set_http_client(&c);
+    api.set_language("de").unwrap();
+    api.set_language_fallback(vec!["fr", "en"]).unwrap();
+
+    // French fails with a 404 and is skipped
+    let series = block_on(api.series(76156)).unwrap().data.unwrap();
+    assert_eq!(series.series_name.unwrap(), "Scrubs – Die Anfänger");
+    assert_eq!(series.overview.unwrap(), "English");
+    assert_eq!(series.language.unwrap().overview.unwrap(), "en");
+}
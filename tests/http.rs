@@ -0,0 +1,168 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tvdb::{DefaultHttpClient, Method, Request, RequestClient, TvdbBuilder};
+
+/// Minimal HTTP/1.1 server on localhost, answering every request with the
+/// given body and keeping connections alive. Records the number of
+/// connections accepted and the request lines and headers received
+struct LocalServer {
+    url: String,
+    connections: Arc<Mutex<u32>>,
+    requests: Arc<Mutex<Vec<Vec<String>>>>,
+}
+
+impl LocalServer {
+    fn start(body: &'static str) -> LocalServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let connections = Arc::new(Mutex::new(0));
+        let requests = Arc::new(Mutex::new(vec![]));
+
+        let (c, r) = (connections.clone(), requests.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                *c.lock().unwrap() += 1;
+                let r = r.clone();
+                thread::spawn(move || serve(stream.unwrap(), body, &r));
+            }
+        });
+
+        LocalServer {
+            url,
+            connections,
+            requests,
+        }
+    }
+
+    fn connections(&self) -> u32 {
+        *self.connections.lock().unwrap()
+    }
+
+    fn requests(&self) -> Vec<Vec<String>> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn serve(stream: TcpStream, body: &str, requests: &Mutex<Vec<Vec<String>>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut stream = stream;
+    loop {
+        let mut lines = vec![];
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            let line = line.trim_end().to_owned();
+            if line.is_empty() {
+                break;
+            }
+            lines.push(line);
+        }
+
+        let length = lines
+            .iter()
+            .filter_map(|l| {
+                l.to_lowercase()
+                    .strip_prefix("content-length:")
+                    .map(|v| v.trim().to_owned())
+            })
+            .next()
+            .map_or(0, |v| v.parse().unwrap());
+        let mut request_body = vec![0; length];
+        reader.read_exact(&mut request_body).unwrap();
+        requests.lock().unwrap().push(lines);
+
+        let resp = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        if stream.write_all(resp.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+#[test]
+fn connections_reused() {
+    let server = LocalServer::start(r#"{"token": "abc.def.ghi", "data": {"id": 76156}}"#);
+
+    let api = TvdbBuilder::new("0629B785CE550C8D")
+        .api_base_url(server.url.as_str())
+        .user_agent("tvdb-test/1.0")
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap();
+    api.series(76156).unwrap();
+    api.series(76156).unwrap();
+    api.clone().series(76156).unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 4);
+    assert!(requests[0][0].starts_with("POST /login "));
+    for r in &requests {
+        assert!(r
+            .iter()
+            .any(|h| h.eq_ignore_ascii_case("user-agent: tvdb-test/1.0")));
+    }
+    assert_eq!(server.connections(), 1);
+}
+
+#[test]
+fn default_user_agent() {
+    let server = LocalServer::start("{}");
+    let client = DefaultHttpClient::new();
+    let resp = client
+        .request(&Request::new(Method::Get, server.url.as_str()))
+        .unwrap();
+    assert_eq!(resp.status, 200);
+
+    let expected = format!("user-agent: {}", tvdb::raw::DEFAULT_USER_AGENT);
+    assert!(server.requests()[0]
+        .iter()
+        .any(|h| h.eq_ignore_ascii_case(&expected)));
+}
+
+/// URL of a server which accepts connections but never responds
+fn silent_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/series/1", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let _streams: Vec<_> = listener.incoming().collect();
+    });
+    url
+}
+
+#[test]
+fn request_timeout() {
+    let client = DefaultHttpClient::new().timeout(Duration::from_millis(200));
+    assert!(client
+        .request(&Request::new(Method::Get, silent_server()))
+        .is_err());
+}
+
+#[test]
+fn default_timeout() {
+    let client = DefaultHttpClient::new();
+    let start = Instant::now();
+    assert!(client
+        .request(&Request::new(Method::Get, silent_server()))
+        .is_err());
+    let elapsed = start.elapsed();
+    assert!(elapsed >= tvdb::raw::DEFAULT_TIMEOUT);
+    assert!(elapsed < tvdb::raw::DEFAULT_TIMEOUT * 2);
+}
+
+#[test]
+fn invalid_proxy() {
+    assert!(DefaultHttpClient::new().proxy("not a url").is_err());
+    assert!(TvdbBuilder::new("0629B785CE550C8D")
+        .proxy("not a url")
+        .build()
+        .is_err());
+}
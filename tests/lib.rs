@@ -1,11 +1,26 @@
+extern crate base64;
 extern crate rand;
+extern crate serde_json;
 extern crate tvdb;
 
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Barrier, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
 use rand::{Rng, SeedableRng};
 
-use tvdb::{EpisodeId, Tvdb, TvdbError, TvdbResult};
+use tvdb::data::SeriesSearchData;
+use tvdb::{
+    Auth, CachePolicy, EpisodeId, EpisodeQuery, FileTokenStore, ImageKeyType, ImageQuery, Method,
+    RateLimit, RatingType, Request, RequestClient, Response, RetryPolicy, Token, TokenStore, Tvdb,
+    TvdbBuilder, TvdbError, TvdbResult,
+};
 
-const APIKEY: &'static str = "0629B785CE550C8D";
+const APIKEY: &str = "0629B785CE550C8D";
 
 #[test]
 fn search() {
@@ -65,18 +80,18 @@ struct DummyRequestClient;
 
 impl DummyRequestClient {
     pub fn new() -> DummyRequestClient {
-        return DummyRequestClient {};
+        DummyRequestClient {}
     }
 }
-use tvdb::RequestClient;
 impl RequestClient for DummyRequestClient {
-    fn get_url(&self, url: &str, jwt_token: Option<String>) -> TvdbResult<String> {
-        return Err(TvdbError::CommunicationError {
+    fn request(&self, request: &Request) -> TvdbResult<Response> {
+        Err(TvdbError::CommunicationError {
             reason: format!(
                 "Fake error while doing fake request for: {:?} with JWT {:?}",
-                url, jwt_token,
+                request.url,
+                request.header_value("Authorization"),
             ),
-        });
+        })
     }
 }
 
@@ -92,12 +107,10 @@ fn custom_http_client() {
 
     match result {
         Ok(_) => panic!("Expected error"),
-        Err(e) => {
-            match e {
-                TvdbError::CommunicationError { reason: _ } => (),
-                _ => panic!("Unexpected"),
-            }
-        }
+        Err(e) => match e {
+            TvdbError::CommunicationError { reason: _ } => (),
+            _ => panic!("Unexpected"),
+        },
     }
 }
 
@@ -113,3 +126,1304 @@ fn all_episodes() {
     let ep = data[0].clone();
     assert!(ep.episode_name.unwrap() == "My First Day");
 }
+
+/// Returns canned responses for known requests, and a 404 response for
+/// anything else. Logging in succeeds unless overridden. Requests are
+/// recorded
+#[derive(Debug)]
+struct CannedRequestClient {
+    responses: HashMap<String, String>,
+    failures: Mutex<Vec<(String, u16)>>,
+    rejected_token: Option<String>,
+    sent: Mutex<Vec<Request>>,
+}
+
+impl CannedRequestClient {
+    pub fn new() -> CannedRequestClient {
+        let mut c = CannedRequestClient {
+            responses: HashMap::new(),
+            failures: Mutex::new(vec![]),
+            rejected_token: None,
+            sent: Mutex::new(vec![]),
+        };
+        c.respond_method(
+            "POST",
+            "https://api.thetvdb.com/login",
+            r#"{"token": "abc.def.ghi"}"#,
+        );
+        c
+    }
+
+    /// All requests made
+    pub fn sent(&self) -> Vec<Request> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// URLs and languages of GET requests made
+    pub fn requests(&self) -> Vec<(String, String)> {
+        self.sent()
+            .into_iter()
+            .filter(|r| r.method == Method::Get)
+            .map(|r| {
+                let lang = r.header_value("Accept-Language").unwrap_or("").to_owned();
+                (r.url, lang)
+            })
+            .collect()
+    }
+
+    pub fn respond(&mut self, url: &str, body: &str) {
+        self.respond_method("GET", url, body);
+    }
+
+    /// Respond to requests with the given HTTP method, e.g `PUT`
+    pub fn respond_method(&mut self, method: &str, url: &str, body: &str) {
+        self.responses
+            .insert(format!("{} {}", method, url), body.into());
+    }
+
+    /// Respond to the next GET request of the URL with a 401 status
+    pub fn unauthorized_once(&self, url: &str) {
+        self.fail_once(url, 401);
+    }
+
+    /// Respond to the next GET request of the URL with the given error
+    /// status. Failures for the same URL are used in the order added
+    pub fn fail_once(&self, url: &str, status: u16) {
+        self.failures
+            .lock()
+            .unwrap()
+            .push((format!("GET {}", url), status));
+    }
+
+    /// Respond to all requests authorized with the given token with a 401
+    /// status
+    pub fn reject_token(&mut self, token: &str) {
+        self.rejected_token = Some(format!("Bearer {}", token));
+    }
+
+    /// Respond only to GET requests in the given language
+    pub fn respond_lang(&mut self, url: &str, language: &str, body: &str) {
+        self.responses
+            .insert(format!("GET {} [{}]", url, language), body.into());
+    }
+}
+
+impl RequestClient for CannedRequestClient {
+    fn request(&self, request: &Request) -> TvdbResult<Response> {
+        self.sent.lock().unwrap().push(request.clone());
+
+        if self.rejected_token.is_some()
+            && request.header_value("Authorization") == self.rejected_token.as_deref()
+        {
+            return Ok(Response::new(401, r#"{"Error": "Not authorized"}"#));
+        }
+
+        let key = format!("{} {}", request.method.as_str(), request.url);
+        {
+            let mut failures = self.failures.lock().unwrap();
+            if let Some(pos) = failures.iter().position(|f| f.0 == key) {
+                let (_, status) = failures.remove(pos);
+                return Ok(Response::new(status, r#"{"Error": "Failed"}"#));
+            }
+        }
+        let lang_key = format!(
+            "{} [{}]",
+            key,
+            request.header_value("Accept-Language").unwrap_or("")
+        );
+        match self
+            .responses
+            .get(&lang_key)
+            .or_else(|| self.responses.get(&key))
+        {
+            Some(body) => Ok(Response::new(200, body.as_str())),
+            None => Ok(Response::new(404, r#"{"Error": "Not found"}"#)),
+        }
+    }
+}
+
+#[test]
+fn series_record() {
+    let api = Tvdb::new(APIKEY);
+    api.login().unwrap();
+    let series = api.series(76156).unwrap().data.unwrap();
+    assert!(series.series_name.unwrap() == "Scrubs");
+    assert!(series.imdb_id.unwrap() == "tt0285403");
+}
+
+#[test]
+fn series_record_canned() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156, "seriesName": "Scrubs", "genre": ["Comedy"],
+            "runtime": "25", "airsDayOfWeek": "Tuesday", "airsTime": "9:00 PM",
+            "networkId": "", "imdbId": "tt0285403", "zap2itId": "SH467536",
+            "added": "", "slug": "scrubs", "siteRating": 8.9, "siteRatingCount": 950,
+            "lastUpdated": 1596500000}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+
+    let series = api.series(76156).unwrap().data.unwrap();
+    assert_eq!(series.series_name.unwrap(), "Scrubs");
+    assert_eq!(series.genre.unwrap(), vec!["Comedy"]);
+    assert_eq!(series.airs_day_of_week.unwrap(), "Tuesday");
+    assert_eq!(series.slug.unwrap(), "scrubs");
+    assert_eq!(series.zap2it_id.unwrap(), "SH467536");
+}
+
+#[test]
+fn series_actors_canned() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156/actors",
+        r#"{"data": [
+            {"id": 27747, "seriesId": 76156, "name": "Zach Braff", "role": "J.D.",
+             "sortOrder": 0, "image": "actors/27747.jpg", "imageAuthor": 1,
+             "imageAdded": "2009-01-23 15:56:09", "lastUpdated": "2017-01-01 00:00:00"},
+            {"id": 27748, "seriesId": 76156, "name": "Donald Faison", "role": "Turk",
+             "sortOrder": 1, "image": "actors/27748.jpg", "imageAuthor": 1,
+             "imageAdded": "2009-01-23 15:56:09", "lastUpdated": "2017-01-01 00:00:00"}
+        ]}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+
+    let actors = api.actors(76156).unwrap().data.unwrap();
+    assert_eq!(actors.len(), 2);
+    assert_eq!(actors[0].name.as_ref().unwrap(), "Zach Braff");
+    assert_eq!(actors[0].role.as_ref().unwrap(), "J.D.");
+    assert_eq!(actors[1].sort_order, Some(1));
+    assert_eq!(actors[1].image.as_ref().unwrap(), "actors/27748.jpg");
+}
+
+#[test]
+fn series_episodes_query_canned() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156/episodes/query?airedSeason=3&airedEpisode=7&page=1",
+        r#"{"data": [
+            {"id": 184764, "airedSeason": 3, "airedEpisodeNumber": 7, "dvdSeason": 3,
+             "dvdEpisodeNumber": 7.0, "absoluteNumber": 53, "episodeName": "My Fifteen Minutes",
+             "firstAired": "2003-11-04", "lastUpdated": 1500000000, "overview": "..."}
+        ],
+        "links": {"first": 1, "last": 1, "next": null, "previous": null}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+
+    let query = EpisodeQuery {
+        aired_season: Some(3),
+        aired_episode: Some(7),
+        ..Default::default()
+    };
+    let eps = api.series_episodes_query(76156, &query, 1).unwrap();
+    let data = eps.data.unwrap();
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].episode_name.as_ref().unwrap(), "My Fifteen Minutes");
+    assert_eq!(data[0].absolute_number, Some(53));
+    assert_eq!(eps.links.unwrap().next, None);
+}
+
+#[test]
+fn season_episode() {
+    let api = Tvdb::new(APIKEY);
+    api.login().unwrap();
+    let sr = api.search(Some("scrubs"), None).unwrap();
+    let ep = api.season_episode(&sr.data.unwrap()[0], 1, 23).unwrap();
+    assert!(ep.episode_name == "My Hero");
+}
+
+#[test]
+fn season_episode_canned() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156/episodes/query?airedSeason=1&airedEpisode=23&page=1",
+        r#"{"data": [{"id": 184623, "airedSeason": 1, "airedEpisodeNumber": 23,
+                      "episodeName": "My Hero"}]}"#,
+    );
+    c.respond(
+        "https://api.thetvdb.com/episodes/184623",
+        r#"{"data": {"id": 184623, "airedSeason": 1, "airedEpisodeNumber": 23,
+                     "episodeName": "My Hero", "seriesId": 76156, "directors": ["Bill Lawrence"]}}"#,
+    );
+    c.respond(
+        "https://api.thetvdb.com/series/76156/episodes/query?airedSeason=1&airedEpisode=99&page=1",
+        r#"{"data": []}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+
+    let ep = api.season_episode(76156, 1, 23).unwrap();
+    assert_eq!(ep.episode_name, "My Hero");
+    assert_eq!(ep.series_id, Some(76156));
+
+    match api.season_episode(76156, 1, 99) {
+        Err(TvdbError::EpisodeNotFound) => (),
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    // Search results without an ID can't be looked up
+    let sr: SeriesSearchData = serde_json::from_str(r#"{"seriesName": "Scrubs"}"#).unwrap();
+    match api.season_episode(&sr, 1, 23) {
+        Err(TvdbError::SeriesNotFound) => (),
+        other => panic!("Unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn series_episodes_summary_canned() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156/episodes/summary",
+        r#"{"data": {"airedSeasons": ["3", "0", "1", "2"], "airedEpisodes": "182",
+                     "dvdSeasons": ["1", "2"], "dvdEpisodes": "48"}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+
+    let summary = api.series_episodes_summary(76156).unwrap().data.unwrap();
+    assert_eq!(summary.aired_seasons, vec![0, 1, 2, 3]);
+    assert_eq!(summary.aired_episodes, Some(182));
+    assert_eq!(summary.dvd_seasons, vec![1, 2]);
+    assert_eq!(summary.dvd_episodes, Some(48));
+}
+
+#[test]
+fn series_images_canned() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156/images",
+        r#"{"data": {"fanart": 30, "poster": 12, "season": 40, "seasonwide": 9, "series": 15}}"#,
+    );
+    c.respond(
+        "https://api.thetvdb.com/series/76156/images/query?keyType=poster&resolution=680x1000",
+        r#"{"data": [
+            {"id": 1, "keyType": "poster", "subKey": "", "fileName": "posters/76156-1.jpg",
+             "resolution": "680x1000", "ratingsInfo": {"average": 7.2, "count": 10},
+             "thumbnail": "_cache/posters/76156-1.jpg", "languageId": 7},
+            {"id": 2, "keyType": "poster", "subKey": "", "fileName": "posters/76156-2.jpg",
+             "resolution": "680x1000", "ratingsInfo": {"average": 8.5, "count": 4},
+             "thumbnail": "_cache/posters/76156-2.jpg", "languageId": 7},
+            {"id": 3, "keyType": "poster", "subKey": "", "fileName": "posters/76156-3.jpg",
+             "resolution": "680x1000", "ratingsInfo": {"average": 0, "count": 0},
+             "thumbnail": "_cache/posters/76156-3.jpg", "languageId": 7}
+        ]}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+
+    let counts = api.series_images(76156).unwrap().data.unwrap();
+    assert_eq!(counts.poster, Some(12));
+    assert_eq!(counts.seasonwide, Some(9));
+
+    let mut query = ImageQuery::new(ImageKeyType::Poster);
+    query.resolution = Some("680x1000".into());
+    let images = api.series_images_query(76156, &query).unwrap();
+    assert_eq!(images.data.as_ref().unwrap().len(), 3);
+    let best = images.highest_rated().unwrap();
+    assert_eq!(best.id, Some(2));
+    assert_eq!(best.file_name.as_ref().unwrap(), "posters/76156-2.jpg");
+}
+
+#[test]
+fn languages_canned() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/languages",
+        r#"{"data": [
+            {"id": 7, "abbreviation": "en", "name": "English", "englishName": "English"},
+            {"id": 14, "abbreviation": "de", "name": "Deutsch", "englishName": "German"}
+        ]}"#,
+    );
+    c.respond(
+        "https://api.thetvdb.com/languages/14",
+        r#"{"data": {"id": 14, "abbreviation": "de", "name": "Deutsch", "englishName": "German"}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+
+    let langs = api.languages().unwrap().data.unwrap();
+    assert_eq!(langs.len(), 2);
+    assert_eq!(langs[1].english_name.as_ref().unwrap(), "German");
+
+    let de = api.language_by_id(14).unwrap().data.unwrap();
+    assert_eq!(de.abbreviation.unwrap(), "de");
+}
+
+#[test]
+fn accept_language() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156, "seriesName": "Scrubs – Die Anfänger"}}"#,
+    );
+    c.respond(
+        "https://api.thetvdb.com/episodes/184603",
+        r#"{"data": {"id": 184603, "episodeName": "Mon mentor"}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.set_language("de").unwrap();
+
+    api.series(76156).unwrap();
+    api.episode(EpisodeId::new(184603, "fr")).unwrap();
+    assert_eq!(
+        c.requests(),
+        vec![
+            (
+                "https://api.thetvdb.com/series/76156".to_owned(),
+                "de".to_owned()
+            ),
+            (
+                "https://api.thetvdb.com/episodes/184603".to_owned(),
+                "fr".to_owned()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn invalid_language() {
+    let c = CannedRequestClient::new();
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+
+    match api.set_language("English") {
+        Err(TvdbError::InvalidLanguage { language }) => assert_eq!(language, "English"),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    assert_eq!(api.language(), "en");
+
+    // Invalid language in an episode ID fails without making a request
+    match api.episode(EpisodeId::new(184603, "EN-us")) {
+        Err(TvdbError::InvalidLanguage { .. }) => (),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    assert!(c.requests().is_empty());
+}
+
+#[test]
+fn language_fallback() {
+    let mut c = CannedRequestClient::new();
+    c.respond_lang(
+        "https://api.thetvdb.com/episodes/184603",
+        "de",
+        r#"{"data": {"id": 184603, "episodeName": "Mein Mentor", "overview": null}}"#,
+    );
+    c.respond_lang(
+        "https://api.thetvdb.com/episodes/184603",
+        "fr",
+        r#"{"data": {"id": 184603, "episodeName": null, "overview": ""}}"#,
+    );
+    c.respond_lang(
+        "https://api.thetvdb.com/episodes/184603",
+        "en",
+        r#"{"data": {"id": 184603, "episodeName": "My Mentor", "overview": "J.D. looks for a mentor"}}"#,
+    );
+    c.respond_lang(
+        "https://api.thetvdb.com/series/76156",
+        "de",
+        r#"{"data": {"id": 76156, "seriesName": "Scrubs – Die Anfänger", "overview": "Deutsch"}}"#,
+    );
+    c.respond_lang(
+        "https://api.thetvdb.com/series/76156/episodes?page=1",
+        "de",
+        r#"{"data": [
+            {"id": 1, "episodeName": "Mein erster Tag", "overview": "Deutsch"},
+            {"id": 2, "episodeName": "", "overview": null}
+        ]}"#,
+    );
+    c.respond_lang(
+        "https://api.thetvdb.com/series/76156/episodes?page=1",
+        "fr",
+        r#"{"data": []}"#,
+    );
+    c.respond_lang(
+        "https://api.thetvdb.com/series/76156/episodes?page=1",
+        "en",
+        r#"{"data": [
+            {"id": 2, "episodeName": "My Mentor", "overview": "English"},
+            {"id": 1, "episodeName": "My First Day", "overview": "English"}
+        ]}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.set_language("de").unwrap();
+    api.set_language_fallback(vec!["fr", "en"]).unwrap();
+
+    // Name from German, overview from English after French is also missing
+    let ep = api
+        .episode(EpisodeId::new(184603, "de"))
+        .unwrap()
+        .data
+        .unwrap();
+    assert_eq!(ep.episode_name, "Mein Mentor");
+    assert_eq!(ep.overview.unwrap(), "J.D. looks for a mentor");
+    let langs = ep.language.unwrap();
+    assert_eq!(langs.episode_name.unwrap(), "de");
+    assert_eq!(langs.overview.unwrap(), "en");
+
+    // Complete records need no further requests
+    let before = c.requests().len();
+    let series = api.series(76156).unwrap().data.unwrap();
+    assert_eq!(c.requests().len(), before + 1);
+    assert_eq!(series.language.unwrap().overview.unwrap(), "de");
+
+    // Listings are matched up by episode ID
+    let eps = api.series_episodes(76156, 1).unwrap().data.unwrap();
+    assert_eq!(eps[0].episode_name.as_ref().unwrap(), "Mein erster Tag");
+    assert_eq!(eps[1].episode_name.as_ref().unwrap(), "My Mentor");
+    assert_eq!(eps[1].overview.as_ref().unwrap(), "English");
+    let langs = eps[1].language.as_ref().unwrap();
+    assert_eq!(langs.episode_name.as_ref().unwrap(), "en");
+    assert_eq!(langs.overview.as_ref().unwrap(), "en");
+}
+
+#[test]
+fn language_fallback_failure_skipped() {
+    let mut c = CannedRequestClient::new();
+    c.respond_lang(
+        "https://api.thetvdb.com/series/76156",
+        "de",
+        r#"{"data": {"id": 76156, "seriesName": "Scrubs – Die Anfänger", "overview": null}}"#,
+    );
+    c.respond_lang(
+        "https://api.thetvdb.com/series/76156",
+        "en",
+        r#"{"data": {"id": 76156, "seriesName": "Scrubs", "overview": "English"}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.set_language("de").unwrap();
+
+    // No French record, so the fallback gets a 404 and moves on to English
+    api.set_language_fallback(vec!["fr", "en"]).unwrap();
+    let series = api.series(76156).unwrap().data.unwrap();
+    assert_eq!(series.series_name.unwrap(), "Scrubs – Die Anfänger");
+    assert_eq!(series.overview.unwrap(), "English");
+    assert_eq!(series.language.unwrap().overview.unwrap(), "en");
+
+    // With no fallback succeeding, the record is returned as fetched
+    api.set_language_fallback(vec!["fr"]).unwrap();
+    let series = api.series(76156).unwrap().data.unwrap();
+    assert_eq!(series.overview, None);
+}
+
+#[test]
+fn updated_since_splits_range() {
+    let week = tvdb::raw::UPDATED_MAX_RANGE;
+    let start = 1_500_000_000;
+
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        &format!(
+            "https://api.thetvdb.com/updated/query?fromTime={}&toTime={}",
+            start,
+            start + week
+        ),
+        r#"{"data": [{"id": 76156, "lastUpdated": 1500000100},
+                     {"id": 80379, "lastUpdated": 1500000200}]}"#,
+    );
+    c.respond(
+        &format!(
+            "https://api.thetvdb.com/updated/query?fromTime={}&toTime={}",
+            start + week,
+            start + 2 * week
+        ),
+        r#"{"data": null}"#,
+    );
+    c.respond(
+        &format!(
+            "https://api.thetvdb.com/updated/query?fromTime={}&toTime={}",
+            start + 2 * week,
+            start + 2 * week + 10
+        ),
+        r#"{"data": [{"id": 76156, "lastUpdated": 1501209605}]}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+
+    let updated = api
+        .updated_since(start, Some(start + 2 * week + 10))
+        .unwrap();
+    assert_eq!(c.requests().len(), 3);
+    let ids: Vec<(u32, u32)> = updated.iter().map(|u| (u.id, u.last_updated)).collect();
+    assert_eq!(ids, vec![(80379, 1500000200), (76156, 1501209605)]);
+
+    // Empty range makes no requests
+    assert!(api.updated_since(start, Some(start)).unwrap().is_empty());
+    assert_eq!(c.requests().len(), 3);
+}
+
+#[test]
+fn user_favorites_canned() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/user",
+        r#"{"data": {"userName": "someone", "language": "en", "favoritesDisplaymode": "banners", "id": 42}}"#,
+    );
+    c.respond(
+        "https://api.thetvdb.com/user/favorites",
+        r#"{"data": {"favorites": [""]}}"#,
+    );
+    c.respond_method(
+        "PUT",
+        "https://api.thetvdb.com/user/favorites/76156",
+        r#"{"data": {"favorites": ["76156"]}}"#,
+    );
+    c.respond_method(
+        "DELETE",
+        "https://api.thetvdb.com/user/favorites/76156",
+        r#"{"data": {"favorites": []}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+
+    let user = api.user().unwrap().data.unwrap();
+    assert_eq!(user.user_name.unwrap(), "someone");
+    assert!(api.favorites().unwrap().data.unwrap().favorites.is_empty());
+    assert_eq!(
+        api.add_favorite(76156).unwrap().data.unwrap().favorites,
+        vec![76156]
+    );
+    assert!(api
+        .remove_favorite(76156)
+        .unwrap()
+        .data
+        .unwrap()
+        .favorites
+        .is_empty());
+}
+
+#[test]
+fn user_ratings_canned() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/user/ratings",
+        r#"{"data": [{"ratingType": "series", "ratingItemId": 76156, "rating": 9},
+                     {"ratingType": "episode", "ratingItemId": 184603, "rating": 7}]}"#,
+    );
+    c.respond(
+        "https://api.thetvdb.com/user/ratings/query?itemType=episode",
+        r#"{"data": [{"ratingType": "episode", "ratingItemId": 184603, "rating": 7}]}"#,
+    );
+    c.respond_method(
+        "PUT",
+        "https://api.thetvdb.com/user/ratings/series/76156/10",
+        r#"{"data": [{"ratingType": "series", "ratingItemId": 76156, "rating": 10}]}"#,
+    );
+    c.respond_method(
+        "DELETE",
+        "https://api.thetvdb.com/user/ratings/series/76156",
+        r#"{"data": []}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+
+    assert_eq!(api.ratings().unwrap().data.unwrap().len(), 2);
+    let eps = api
+        .ratings_query(RatingType::Episode)
+        .unwrap()
+        .data
+        .unwrap();
+    assert_eq!(eps[0].rating_item_id, Some(184603));
+    let added = api
+        .add_rating(RatingType::Series, 76156, 10)
+        .unwrap()
+        .data
+        .unwrap();
+    assert_eq!(added[0].rating, Some(10.0));
+    assert!(api
+        .delete_rating(RatingType::Series, 76156)
+        .unwrap()
+        .data
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn login_through_client() {
+    let mut c = CannedRequestClient::new();
+    c.respond_method(
+        "POST",
+        "https://api.thetvdb.com/login",
+        r#"{"token": "abc.def.ghi"}"#,
+    );
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.login().unwrap();
+    api.series(76156).unwrap();
+
+    let sent = c.sent();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[0].method, Method::Post);
+    assert_eq!(
+        sent[0].header_value("content-type"),
+        Some("application/json")
+    );
+    let body: serde_json::Value = serde_json::from_str(sent[0].body.as_ref().unwrap()).unwrap();
+    assert_eq!(body["apikey"], APIKEY);
+    assert!(body.get("username").is_none());
+    assert_eq!(sent[0].header_value("Authorization"), None);
+    assert_eq!(
+        sent[1].header_value("Authorization"),
+        Some("Bearer abc.def.ghi")
+    );
+}
+
+#[test]
+fn unsuccessful_response() {
+    let c = CannedRequestClient::new();
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+
+    match api.series(1) {
+        Err(TvdbError::CommunicationError { reason }) => assert!(reason.contains("404")),
+        other => panic!("Unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn login_with_user() {
+    let mut c = CannedRequestClient::new();
+    c.respond_method(
+        "POST",
+        "https://api.thetvdb.com/login",
+        r#"{"token": "abc.def.ghi"}"#,
+    );
+
+    let mut api = Tvdb::new(Auth::new(APIKEY).with_user("someone", "ABCDEF0123456789"));
+    api.set_http_client(&c);
+    api.login().unwrap();
+
+    let sent = c.sent();
+    let body: serde_json::Value = serde_json::from_str(sent[0].body.as_ref().unwrap()).unwrap();
+    assert_eq!(body["apikey"], APIKEY);
+    assert_eq!(body["username"], "someone");
+    assert_eq!(body["userkey"], "ABCDEF0123456789");
+}
+
+/// Unsigned JWT expiring the given number of seconds from now
+fn jwt_expiring_in(secs: i64) -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let payload = format!(r#"{{"exp": {}}}"#, now + secs);
+    format!(
+        "eyJhbGciOiJIUzI1NiJ9.{}.c2ln",
+        base64::encode_config(&payload, base64::URL_SAFE_NO_PAD)
+    )
+}
+
+#[test]
+fn token_refreshed_before_expiry() {
+    let soon = jwt_expiring_in(60);
+    let later = jwt_expiring_in(24 * 60 * 60);
+
+    let mut c = CannedRequestClient::new();
+    c.respond_method(
+        "POST",
+        "https://api.thetvdb.com/login",
+        &format!(r#"{{"token": "{}"}}"#, soon),
+    );
+    c.respond(
+        "https://api.thetvdb.com/refresh_token",
+        &format!(r#"{{"token": "{}"}}"#, later),
+    );
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.login().unwrap();
+    assert!(api.token_expiry().is_some());
+    api.series(76156).unwrap();
+
+    let sent = c.sent();
+    assert_eq!(sent.len(), 3);
+    assert_eq!(sent[1].url, "https://api.thetvdb.com/refresh_token");
+    assert_eq!(
+        sent[1].header_value("Authorization"),
+        Some(format!("Bearer {}", soon).as_str())
+    );
+    assert_eq!(
+        sent[2].header_value("Authorization"),
+        Some(format!("Bearer {}", later).as_str())
+    );
+}
+
+#[test]
+fn expired_token_logs_in_again() {
+    let expired = jwt_expiring_in(-60);
+
+    let mut c = CannedRequestClient::new();
+    c.respond_method(
+        "POST",
+        "https://api.thetvdb.com/login",
+        &format!(r#"{{"token": "{}"}}"#, expired),
+    );
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.login().unwrap();
+    api.series(76156).unwrap();
+
+    let methods: Vec<Method> = c.sent().into_iter().map(|r| r.method).collect();
+    assert_eq!(methods, vec![Method::Post, Method::Post, Method::Get]);
+}
+
+#[test]
+fn unauthorized_retried_after_login() {
+    let mut c = CannedRequestClient::new();
+    c.respond_method(
+        "POST",
+        "https://api.thetvdb.com/login",
+        r#"{"token": "abc.def.ghi"}"#,
+    );
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+    c.unauthorized_once("https://api.thetvdb.com/series/76156");
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.login().unwrap();
+    assert_eq!(api.token_expiry(), None);
+    api.series(76156).unwrap();
+
+    let methods: Vec<Method> = c.sent().into_iter().map(|r| r.method).collect();
+    assert_eq!(
+        methods,
+        vec![Method::Post, Method::Get, Method::Post, Method::Get]
+    );
+}
+
+#[test]
+fn login_on_first_request() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.series(76156).unwrap();
+    api.series(76156).unwrap();
+
+    let sent = c.sent();
+    let methods: Vec<Method> = sent.iter().map(|r| r.method).collect();
+    assert_eq!(methods, vec![Method::Post, Method::Get, Method::Get]);
+    assert_eq!(
+        sent[1].header_value("Authorization"),
+        Some("Bearer abc.def.ghi")
+    );
+}
+
+#[test]
+fn auto_login_disabled() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.set_auto_login(false);
+    api.series(76156).unwrap();
+
+    let sent = c.sent();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].header_value("Authorization"), None);
+}
+
+#[test]
+fn auto_login_disabled_keeps_expired_token() {
+    let expired = jwt_expiring_in(-60);
+
+    let mut c = CannedRequestClient::new();
+    c.respond_method(
+        "POST",
+        "https://api.thetvdb.com/login",
+        &format!(r#"{{"token": "{}"}}"#, expired),
+    );
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.set_auto_login(false);
+    api.login().unwrap();
+    api.series(76156).unwrap();
+
+    let sent = c.sent();
+    let methods: Vec<Method> = sent.iter().map(|r| r.method).collect();
+    assert_eq!(methods, vec![Method::Post, Method::Get]);
+    assert_eq!(
+        sent[1].header_value("Authorization"),
+        Some(format!("Bearer {}", expired).as_str())
+    );
+}
+
+#[test]
+fn auto_login_disabled_unauthorized_not_retried() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+    c.unauthorized_once("https://api.thetvdb.com/series/76156");
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.set_auto_login(false);
+    api.login().unwrap();
+    assert!(api.series(76156).is_err());
+
+    let methods: Vec<Method> = c.sent().into_iter().map(|r| r.method).collect();
+    assert_eq!(methods, vec![Method::Post, Method::Get]);
+}
+
+/// Path of a not yet existing file in the temp dir
+fn scratch_file(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("tvdb-rs-test-{}-{}", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn file_token_store() {
+    let store = FileTokenStore::new(scratch_file("token-store"));
+    assert_eq!(store.load().unwrap(), None);
+
+    let valid = Token::new(jwt_expiring_in(60 * 60));
+    store.save(&valid).unwrap();
+    assert_eq!(store.load().unwrap(), Some(valid));
+
+    store.save(&Token::new(jwt_expiring_in(-60))).unwrap();
+    assert_eq!(store.load().unwrap(), None);
+
+    fs::remove_file(store.path()).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn file_token_store_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let store = FileTokenStore::new(scratch_file("token-permissions"));
+    store.save(&Token::new(jwt_expiring_in(60 * 60))).unwrap();
+    let mode = fs::metadata(store.path()).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    fs::remove_file(store.path()).unwrap();
+}
+
+#[test]
+fn stored_token_reused() {
+    let stored = jwt_expiring_in(24 * 60 * 60);
+    let store = FileTokenStore::new(scratch_file("token-reused"));
+    store.save(&Token::new(stored.clone())).unwrap();
+
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.set_token_store(&store);
+    api.series(76156).unwrap();
+
+    let sent = c.sent();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(
+        sent[0].header_value("Authorization"),
+        Some(format!("Bearer {}", stored).as_str())
+    );
+
+    fs::remove_file(store.path()).unwrap();
+}
+
+#[test]
+fn login_saves_token() {
+    let store = FileTokenStore::new(scratch_file("token-saved"));
+
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.set_token_store(&store);
+    api.series(76156).unwrap();
+
+    assert_eq!(c.sent()[0].method, Method::Post);
+    assert_eq!(fs::read_to_string(store.path()).unwrap(), "abc.def.ghi");
+
+    fs::remove_file(store.path()).unwrap();
+}
+
+#[test]
+fn unsaved_token_still_used() {
+    // Saving fails, as the directory doesn't exist
+    let store = FileTokenStore::new(scratch_file("missing-dir").join("token"));
+
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.set_token_store(&store);
+    api.series(76156).unwrap();
+    api.series(76156).unwrap();
+
+    let sent = c.sent();
+    assert_eq!(sent.len(), 3);
+    assert_eq!(
+        sent[2].header_value("Authorization"),
+        Some("Bearer abc.def.ghi")
+    );
+}
+
+#[test]
+fn unreadable_token_store_ignored() {
+    let path = scratch_file("token-invalid");
+    fs::write(&path, b"\xff\xfe not utf-8").unwrap();
+    let store = FileTokenStore::new(&path);
+
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.set_token_store(&store);
+    api.series(76156).unwrap();
+
+    let sent = c.sent();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[0].method, Method::Post);
+    assert_eq!(
+        sent[1].header_value("Authorization"),
+        Some("Bearer abc.def.ghi")
+    );
+}
+
+#[test]
+fn shared_between_threads() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Tvdb<'static>>();
+
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+
+    thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for _ in 0..5 {
+                    api.series(76156).unwrap();
+                }
+            });
+        }
+    });
+
+    let sent = c.sent();
+    assert_eq!(sent.iter().filter(|r| r.method == Method::Post).count(), 1);
+    assert_eq!(sent.len(), 21);
+}
+
+/// Holds back requests authorized with the given token until all the
+/// expected ones have been made, so they are answered at the same time
+#[derive(Debug)]
+struct GatheringRequestClient<'c> {
+    inner: &'c CannedRequestClient,
+    authorization: String,
+    barrier: Barrier,
+}
+
+impl<'c> RequestClient for GatheringRequestClient<'c> {
+    fn request(&self, request: &Request) -> TvdbResult<Response> {
+        if request.header_value("Authorization") == Some(self.authorization.as_str()) {
+            self.barrier.wait();
+        }
+        self.inner.request(request)
+    }
+}
+
+#[test]
+fn rejected_token_replaced_once() {
+    let stored = jwt_expiring_in(24 * 60 * 60);
+    let store = FileTokenStore::new(scratch_file("token-rejected"));
+    store.save(&Token::new(stored.clone())).unwrap();
+
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+    c.reject_token(&stored);
+
+    let gathering = GatheringRequestClient {
+        inner: &c,
+        authorization: format!("Bearer {}", stored),
+        barrier: Barrier::new(8),
+    };
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&gathering);
+    api.set_token_store(&store);
+
+    // Threads rejected at the same time log in only once between them
+    thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| api.series(76156).unwrap());
+        }
+    });
+
+    let sent = c.sent();
+    assert_eq!(sent.iter().filter(|r| r.method == Method::Post).count(), 1);
+
+    fs::remove_file(store.path()).unwrap();
+}
+
+#[test]
+fn custom_base_urls() {
+    let mut c = CannedRequestClient::new();
+    c.respond_method(
+        "POST",
+        "http://localhost:8080/tvdb/login",
+        r#"{"token": "abc.def.ghi"}"#,
+    );
+    c.respond(
+        "http://localhost:8080/tvdb/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+    c.respond(
+        "http://localhost:8080/tvdb/search/series?name=scrubs",
+        r#"{"data": [{"id": 76156, "seriesName": "Scrubs"}]}"#,
+    );
+
+    let mut api = Tvdb::new(APIKEY);
+    api.set_http_client(&c);
+    api.set_api_base_url("http://localhost:8080/tvdb/").unwrap();
+    api.set_artwork_base_url("http://localhost:8080/banners");
+    assert_eq!(api.api_base_url(), "http://localhost:8080/tvdb");
+    assert_eq!(
+        api.artwork_host().base_url(),
+        "http://localhost:8080/banners/"
+    );
+
+    api.series(76156).unwrap();
+    api.search(Some("scrubs"), None).unwrap();
+
+    let urls: Vec<String> = c.sent().into_iter().map(|r| r.url).collect();
+    assert_eq!(
+        urls,
+        vec![
+            "http://localhost:8080/tvdb/login",
+            "http://localhost:8080/tvdb/series/76156",
+            "http://localhost:8080/tvdb/search/series?name=scrubs",
+        ]
+    );
+}
+
+#[test]
+fn invalid_base_url() {
+    let mut api = Tvdb::new(APIKEY);
+    assert!(api.set_api_base_url("api.example.com").is_err());
+    assert!(api.set_api_base_url("ftp://api.example.com").is_err());
+    assert_eq!(api.api_base_url(), "https://api.thetvdb.com");
+
+    assert!(TvdbBuilder::new(APIKEY)
+        .api_base_url("api.example.com")
+        .build()
+        .is_err());
+}
+
+#[test]
+fn builder_configures_tvdb() {
+    let mut c = CannedRequestClient::new();
+    c.respond_method(
+        "POST",
+        "http://localhost:8080/login",
+        r#"{"token": "abc.def.ghi"}"#,
+    );
+    c.respond(
+        "http://localhost:8080/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+
+    let api = TvdbBuilder::new(APIKEY)
+        .user("someone", "ABCDEF0123456789")
+        .language("de")
+        .api_base_url("http://localhost:8080")
+        .http_client(&c)
+        .build()
+        .unwrap();
+    assert_eq!(api.language(), "de");
+    assert_eq!(api.auth().username.as_deref(), Some("someone"));
+    api.series(76156).unwrap();
+
+    let sent = c.sent();
+    let body: serde_json::Value = serde_json::from_str(sent[0].body.as_ref().unwrap()).unwrap();
+    assert_eq!(body["username"], "someone");
+    assert_eq!(sent[1].header_value("Accept-Language"), Some("de"));
+}
+
+#[test]
+fn builder_invalid_language() {
+    match Tvdb::builder(APIKEY).language("german").build() {
+        Err(TvdbError::InvalidLanguage { .. }) => (),
+        other => panic!("Unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn retry_server_errors() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+    c.fail_once("https://api.thetvdb.com/series/76156", 503);
+    c.fail_once("https://api.thetvdb.com/series/76156", 500);
+
+    let api = TvdbBuilder::new(APIKEY)
+        .http_client(&c)
+        .retry_policy(RetryPolicy::new(3).delay(Duration::from_millis(1)))
+        .build()
+        .unwrap();
+    api.series(76156).unwrap();
+    assert_eq!(c.requests().len(), 3);
+
+    c.fail_once("https://api.thetvdb.com/series/76156", 404);
+    assert!(api.series(76156).is_err());
+    assert_eq!(c.requests().len(), 4);
+}
+
+#[test]
+fn cache_responses() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+    c.respond_method(
+        "PUT",
+        "https://api.thetvdb.com/user/favorites/76156",
+        r#"{"data": {"favorites": ["76156"]}}"#,
+    );
+
+    let api = TvdbBuilder::new(APIKEY)
+        .http_client(&c)
+        .cache_policy(CachePolicy::new(Duration::from_secs(60)))
+        .build()
+        .unwrap();
+    api.series(76156).unwrap();
+    api.series(76156).unwrap();
+    assert_eq!(c.requests().len(), 1);
+
+    // Modifying data clears the cache
+    api.add_favorite(76156).unwrap();
+    api.series(76156).unwrap();
+    assert_eq!(c.requests().len(), 2);
+}
+
+#[test]
+fn set_http_client_replaces_builder_policies() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+    let mut other = CannedRequestClient::new();
+    other.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+
+    let mut api = TvdbBuilder::new(APIKEY)
+        .http_client(&c)
+        .cache_policy(CachePolicy::new(Duration::from_secs(60)))
+        .build()
+        .unwrap();
+    api.set_http_client(&other);
+
+    // Requests no longer go through the cache
+    api.series(76156).unwrap();
+    api.series(76156).unwrap();
+    assert_eq!(c.requests().len(), 0);
+    assert_eq!(other.requests().len(), 2);
+}
+
+#[test]
+fn rate_limit_shared_by_clones() {
+    let mut c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/76156",
+        r#"{"data": {"id": 76156}}"#,
+    );
+
+    let api = TvdbBuilder::new(APIKEY)
+        .http_client(&c)
+        .rate_limit(RateLimit::new(20.0).unwrap().burst(2))
+        .build()
+        .unwrap();
+    let other = api.clone();
+
+    // Each clone logs in, and only the first two requests are within the
+    // burst, so the remaining four wait 50ms each
+    let start = Instant::now();
+    api.series(76156).unwrap();
+    other.series(76156).unwrap();
+    api.series(76156).unwrap();
+    other.series(76156).unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(190));
+    assert_eq!(c.sent().len(), 6);
+}
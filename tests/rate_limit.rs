@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+use tvdb::rate_limit::{RateLimit, RateLimiter};
+
+#[test]
+fn burst_then_wait() {
+    let limiter = RateLimiter::new(RateLimit::new(10.0).unwrap().burst(2));
+    assert_eq!(limiter.reserve(), Duration::from_secs(0));
+    assert_eq!(limiter.reserve(), Duration::from_secs(0));
+
+    // Each further request waits for another token, in order
+    let d = limiter.reserve();
+    assert!(d > Duration::from_millis(80) && d <= Duration::from_millis(100));
+    let d = limiter.reserve();
+    assert!(d > Duration::from_millis(180) && d <= Duration::from_millis(200));
+}
+
+#[test]
+fn default_burst() {
+    assert_eq!(
+        RateLimit::new(5.0).unwrap(),
+        RateLimit::new(5.0).unwrap().burst(5)
+    );
+    assert_eq!(
+        RateLimit::new(0.5).unwrap(),
+        RateLimit::new(0.5).unwrap().burst(1)
+    );
+}
+
+#[test]
+fn invalid_rate_rejected() {
+    assert!(RateLimit::new(0.0).is_err());
+    assert!(RateLimit::new(-1.0).is_err());
+    assert!(RateLimit::new(f64::NAN).is_err());
+    assert!(RateLimit::new(f64::INFINITY).is_err());
+}
+
+#[test]
+fn tiny_rate_waits_without_overflow() {
+    let limiter = RateLimiter::new(RateLimit::new(1e-300).unwrap());
+    assert_eq!(limiter.reserve(), Duration::from_secs(0));
+    assert_eq!(limiter.reserve(), Duration::MAX);
+}
+
+#[test]
+fn acquire_waits() {
+    let limiter = RateLimiter::new(RateLimit::new(20.0).unwrap().burst(1));
+    let start = Instant::now();
+    for _ in 0..4 {
+        limiter.acquire();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(140));
+}
@@ -0,0 +1,95 @@
+use std::time::{Duration, SystemTime};
+
+use tvdb::{Response, RetryPolicy, TvdbError, TvdbResult};
+
+fn status(status: u16) -> TvdbResult<Response> {
+    Ok(Response::new(status, ""))
+}
+
+fn retry_after(status: u16, value: &str) -> TvdbResult<Response> {
+    let mut resp = Response::new(status, "");
+    resp.headers.push(("Retry-After".into(), value.into()));
+    Ok(resp)
+}
+
+fn connection_reset() -> TvdbResult<Response> {
+    Err(TvdbError::CommunicationError {
+        reason: "connection reset".into(),
+    })
+}
+
+#[test]
+fn exponential_backoff() {
+    let policy = RetryPolicy::new(6)
+        .delay(Duration::from_millis(100))
+        .max_delay(Duration::from_millis(1000))
+        .jitter(false);
+
+    let delays: Vec<Option<Duration>> = (1..7)
+        .map(|n| policy.retry_delay(n, &status(503)))
+        .collect();
+    assert_eq!(
+        delays,
+        vec![
+            Some(Duration::from_millis(100)),
+            Some(Duration::from_millis(200)),
+            Some(Duration::from_millis(400)),
+            Some(Duration::from_millis(800)),
+            Some(Duration::from_millis(1000)),
+            None,
+        ]
+    );
+}
+
+#[test]
+fn jitter_within_range() {
+    let policy = RetryPolicy::new(3).delay(Duration::from_millis(100));
+    for _ in 0..100 {
+        let d = policy.retry_delay(2, &status(500)).unwrap();
+        assert!(d >= Duration::from_millis(100) && d <= Duration::from_millis(200));
+    }
+}
+
+#[test]
+fn retryable_classification() {
+    let policy = RetryPolicy::new(3);
+    assert!(policy.is_retryable(&status(429)));
+    assert!(policy.is_retryable(&status(502)));
+    assert!(policy.is_retryable(&connection_reset()));
+    assert!(!policy.is_retryable(&status(200)));
+    assert!(!policy.is_retryable(&status(404)));
+    assert!(!policy.is_retryable(&Err(TvdbError::SeriesNotFound)));
+
+    let policy = RetryPolicy::new(3)
+        .retry_statuses(vec![404])
+        .retry_errors(false);
+    assert!(policy.is_retryable(&status(404)));
+    assert!(!policy.is_retryable(&status(503)));
+    assert!(!policy.is_retryable(&connection_reset()));
+
+    assert_eq!(RetryPolicy::none().retry_delay(1, &status(503)), None);
+}
+
+#[test]
+fn retry_after_header() {
+    let policy = RetryPolicy::new(3).max_delay(Duration::from_secs(60));
+    assert_eq!(
+        policy.retry_delay(1, &retry_after(429, "5")),
+        Some(Duration::from_secs(5))
+    );
+
+    // HTTP date in the past retries immediately
+    assert_eq!(
+        policy.retry_delay(1, &retry_after(503, "Wed, 21 Oct 2015 07:28:00 GMT")),
+        Some(Duration::from_secs(0))
+    );
+
+    let at = SystemTime::now() + Duration::from_secs(30);
+    let d = policy
+        .retry_delay(1, &retry_after(503, &httpdate::fmt_http_date(at)))
+        .unwrap();
+    assert!(d > Duration::from_secs(25) && d <= Duration::from_secs(30));
+
+    // Waiting longer than the maximum delay gives up
+    assert_eq!(policy.retry_delay(1, &retry_after(429, "3600")), None);
+}
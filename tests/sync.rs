@@ -0,0 +1,180 @@
+extern crate tvdb;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tvdb::sync::SeriesSync;
+use tvdb::{Request, RequestClient, Response, Tvdb, TvdbResult};
+
+/// Returns canned responses for known URLs, which can be changed between
+/// requests, and a 404 response for anything else. Requested URLs are
+/// recorded
+#[derive(Debug)]
+struct CannedRequestClient {
+    responses: Mutex<HashMap<String, String>>,
+    requests: Mutex<Vec<String>>,
+}
+
+impl CannedRequestClient {
+    pub fn new() -> CannedRequestClient {
+        CannedRequestClient {
+            responses: Mutex::new(HashMap::new()),
+            requests: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn respond(&self, url: &str, body: &str) {
+        self.responses
+            .lock()
+            .unwrap()
+            .insert(url.into(), body.into());
+    }
+
+    pub fn take_requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().drain(..).collect()
+    }
+}
+
+impl RequestClient for CannedRequestClient {
+    fn request(&self, request: &Request) -> TvdbResult<Response> {
+        self.requests.lock().unwrap().push(request.url.clone());
+        match self.responses.lock().unwrap().get(&request.url) {
+            Some(body) => Ok(Response::new(200, body.as_str())),
+            None => Ok(Response::new(404, "")),
+        }
+    }
+}
+
+const START: u32 = 1_500_000_000;
+
+#[test]
+fn sync_changes() {
+    let c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/1/episodes?page=1",
+        r#"{"data": [{"id": 11, "lastUpdated": 100}, {"id": 12, "lastUpdated": 100}],
+            "links": {"first": 1, "last": 2, "next": 2, "previous": null}}"#,
+    );
+    c.respond(
+        "https://api.thetvdb.com/series/1/episodes?page=2",
+        r#"{"data": [{"id": 13, "lastUpdated": 100}],
+            "links": {"first": 1, "last": 2, "next": null, "previous": 1}}"#,
+    );
+    c.respond(
+        "https://api.thetvdb.com/series/2/episodes?page=1",
+        r#"{"data": [{"id": 21, "lastUpdated": 100}]}"#,
+    );
+
+    let mut api = Tvdb::new("");
+    api.set_http_client(&c);
+    api.set_auto_login(false);
+
+    // First sync fetches everything, without needing the updated feed
+    let mut sync = SeriesSync::new(vec![1, 2], START);
+    let changes = sync.sync_until(&api, START + 10).unwrap();
+    assert_eq!(changes.series.len(), 2);
+    assert_eq!(changes.series[0].series_id, 1);
+    assert_eq!(changes.series[0].added.len(), 3);
+    assert_eq!(changes.series[1].added.len(), 1);
+    assert_eq!(sync.last_sync(), START + 10);
+    assert_eq!(c.take_requests().len(), 3);
+
+    // Series 1 changed: episode 12 modified, 13 removed, 14 added
+    c.respond(
+        &format!(
+            "https://api.thetvdb.com/updated/query?fromTime={}&toTime={}",
+            START + 10,
+            START + 20
+        ),
+        r#"{"data": [{"id": 1, "lastUpdated": 1500000015}, {"id": 99, "lastUpdated": 1500000016}]}"#,
+    );
+    c.respond(
+        "https://api.thetvdb.com/series/1/episodes?page=1",
+        r#"{"data": [{"id": 11, "lastUpdated": 100}, {"id": 12, "lastUpdated": 200},
+                     {"id": 14, "lastUpdated": 200}]}"#,
+    );
+
+    let changes = sync.sync_until(&api, START + 20).unwrap();
+    assert_eq!(
+        c.take_requests(),
+        vec![
+            format!(
+                "https://api.thetvdb.com/updated/query?fromTime={}&toTime={}",
+                START + 10,
+                START + 20
+            ),
+            "https://api.thetvdb.com/series/1/episodes?page=1".to_owned(),
+        ]
+    );
+    assert_eq!(changes.series.len(), 1);
+    let series = &changes.series[0];
+    assert_eq!(series.series_id, 1);
+    assert_eq!(series.added.len(), 1);
+    assert_eq!(series.added[0].id, Some(14));
+    assert_eq!(series.modified.len(), 1);
+    assert_eq!(series.modified[0].id, Some(12));
+    assert_eq!(series.removed, vec![13]);
+}
+
+#[test]
+fn failed_sync_keeps_state() {
+    let c = CannedRequestClient::new();
+    let mut api = Tvdb::new("");
+    api.set_http_client(&c);
+    api.set_auto_login(false);
+
+    let mut sync = SeriesSync::new(vec![1], START);
+    sync.set_known_episodes(1, vec![(11, Some(100))]);
+
+    // Updated feed fails
+    assert!(sync.sync_until(&api, START + 10).is_err());
+    assert_eq!(sync.last_sync(), START);
+    assert_eq!(sync.known_episodes(1).unwrap().len(), 1);
+
+    // Nothing changed
+    c.respond(
+        &format!(
+            "https://api.thetvdb.com/updated/query?fromTime={}&toTime={}",
+            START,
+            START + 10
+        ),
+        r#"{"data": null}"#,
+    );
+    let changes = sync.sync_until(&api, START + 10).unwrap();
+    assert!(changes.is_empty());
+    assert_eq!(sync.last_sync(), START + 10);
+}
+
+#[test]
+fn series_not_found() {
+    let c = CannedRequestClient::new();
+    c.respond(
+        "https://api.thetvdb.com/series/2/episodes?page=1",
+        r#"{"data": [{"id": 21, "lastUpdated": 100}]}"#,
+    );
+    c.respond(
+        &format!(
+            "https://api.thetvdb.com/updated/query?fromTime={}&toTime={}",
+            START,
+            START + 10
+        ),
+        r#"{"data": [{"id": 1, "lastUpdated": 1500000005}]}"#,
+    );
+
+    let mut api = Tvdb::new("");
+    api.set_http_client(&c);
+    api.set_auto_login(false);
+
+    // Series 1 has no episodes (404), which doesn't stop series 2 syncing
+    let mut sync = SeriesSync::new(vec![1, 2], START);
+    sync.set_known_episodes(1, vec![(11, Some(100)), (12, Some(100))]);
+    let changes = sync.sync_until(&api, START + 10).unwrap();
+    assert_eq!(changes.series.len(), 2);
+    assert_eq!(changes.series[0].series_id, 1);
+    assert!(changes.series[0].added.is_empty());
+    assert_eq!(changes.series[0].removed, vec![11, 12]);
+    assert_eq!(changes.series[1].series_id, 2);
+    assert_eq!(changes.series[1].added.len(), 1);
+    assert_eq!(sync.known_episodes(1).unwrap().len(), 0);
+    assert_eq!(sync.last_sync(), START + 10);
+}
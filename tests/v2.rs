@@ -1,7 +1,7 @@
 extern crate tvdb;
 use tvdb::raw::Tvdb;
 
-static APIKEY: &'static str = "0629B785CE550C8D";
+static APIKEY: &str = "0629B785CE550C8D";
 
 #[test]
 fn basic() {